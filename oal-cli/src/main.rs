@@ -1,35 +1,401 @@
+mod manifest;
+mod mock;
+mod repl;
+mod session;
+mod traffic;
+
+#[cfg(test)]
+mod manifest_tests;
+#[cfg(test)]
+mod mock_tests;
+#[cfg(test)]
+mod repl_tests;
+#[cfg(test)]
+mod session_tests;
+#[cfg(test)]
+mod tests;
+#[cfg(test)]
+mod traffic_tests;
+#[cfg(test)]
+mod watch_tests;
+
+use crate::manifest::{Format, Manifest, MANIFEST};
+use crate::session::{Session, IT};
 use anyhow::anyhow;
 use clap::Parser as ClapParser;
+use notify::{DebouncedEvent, Watcher};
+use oal_codegen::SourceMap;
+use oal_compiler::hoist::{hoist, Hoisting};
+use oal_compiler::lint::Level;
+use oal_compiler::resolver::{DirResolver, Resolvers};
 use oal_compiler::{Locator, ModuleSet, Program};
+use openapiv3::OpenAPI;
+use std::cell::RefCell;
+use std::collections::HashSet;
 
 /// Compiles a program into an OpenAPI description in YAML.
 #[derive(ClapParser, Debug)]
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    #[clap(flatten)]
+    compile: CompileArgs,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Decompiles an OpenAPI description into a program
+    Import(ImportArgs),
+    /// Builds the targets of a project manifest
+    Build(BuildArgs),
+    /// Compiles programs again whenever one of their sources changes
+    Watch(CompileArgs),
+    /// Evaluates declarations and expressions interactively
+    Repl(ReplArgs),
+    /// Prints the OpenAPI fragment of an expression or declaration of a program
+    Eval(EvalArgs),
+    /// Checks that programs compile, without generating any output
+    Check(CheckArgs),
+    /// Compares two versions of a program and reports breaking changes
+    Diff(DiffArgs),
+    /// Checks programs against API design rules
+    Lint(LintArgs),
+    /// Serves example responses for the relations of a program
+    Mock(MockArgs),
+    /// Checks recorded HTTP traffic against the relations of a program
+    VerifyTraffic(VerifyTrafficArgs),
+    /// Infers the declaration of a schema from sample JSON payloads
+    InferSchema(InferSchemaArgs),
+}
+
+/// Builds the targets of a project manifest.
+#[derive(clap::Args, Debug)]
+struct BuildArgs {
+    /// The path to the project manifest
+    #[clap(short = 'm', long = "manifest", parse(from_os_str), default_value = MANIFEST)]
+    manifest: std::path::PathBuf,
+
+    /// The name of a target to build, instead of all targets (repeatable)
+    #[clap(short = 't', long = "target")]
+    targets: Vec<String>,
+}
+
+/// Decompiles an OpenAPI description into a program.
+#[derive(clap::Args, Debug)]
+struct ImportArgs {
+    /// The path to the OpenAPI description, in YAML or JSON
+    #[clap(parse(from_os_str))]
+    input: std::path::PathBuf,
+
+    /// The path to the output program
+    #[clap(short = 'o', long = "output", parse(from_os_str))]
+    output: std::path::PathBuf,
+
+    /// The path to a base OpenAPI description to extract, without paths and components
+    #[clap(short = 'b', long = "base", parse(from_os_str))]
+    base: Option<std::path::PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+struct CompileArgs {
+    /// The path to the source program (repeatable, sharing the loaded modules)
+    #[clap(short = 'i', long = "input", parse(from_os_str), required = true)]
+    inputs: Vec<std::path::PathBuf>,
+
+    /// The path to the output OpenAPI description (a directory if splitting by module),
+    /// given once per input
+    #[clap(short = 'o', long = "output", parse(from_os_str), required = true)]
+    outputs: Vec<std::path::PathBuf>,
+
+    #[clap(flatten)]
+    generate: GenerateArgs,
+
+    #[clap(flatten)]
+    load: LoadArgs,
+}
+
+#[derive(clap::Args, Debug)]
+struct LoadArgs {
+    /// Resolves the modules of a URL scheme from a local directory, as SCHEME=DIR (repeatable)
+    #[clap(long = "scheme", parse(try_from_str = parse_scheme))]
+    schemes: Vec<(String, std::path::PathBuf)>,
+
+    /// A directory in which to look up imported modules, before those of OAL_PATH (repeatable)
+    #[clap(short = 'L', long = "lib-path", parse(from_os_str))]
+    lib_paths: Vec<std::path::PathBuf>,
+}
+
+/// Checks that programs compile, without generating any output.
+#[derive(clap::Args, Debug)]
+struct CheckArgs {
+    /// The path to the source program (repeatable)
+    #[clap(short = 'i', long = "input", parse(from_os_str), required = true)]
+    inputs: Vec<std::path::PathBuf>,
+
+    /// Also generates the OpenAPI description in memory and validates it
+    #[clap(long = "validate")]
+    validate: bool,
+
+    /// The path to a base OpenAPI description to validate with
+    #[clap(short = 'b', long = "base", parse(from_os_str), requires = "validate")]
+    base: Option<std::path::PathBuf>,
+
+    /// The path to an OpenAPI Overlay to validate with (repeatable)
+    #[clap(long = "overlay", parse(from_os_str), requires = "validate")]
+    overlays: Vec<std::path::PathBuf>,
+
+    #[clap(flatten)]
+    load: LoadArgs,
+}
+
+/// Checks programs against API design rules.
+#[derive(clap::Args, Debug)]
+struct LintArgs {
+    /// The path to the source program (repeatable), instead of the targets of the manifest
+    #[clap(short = 'i', long = "input", parse(from_os_str))]
+    inputs: Vec<std::path::PathBuf>,
+
+    /// The path to the project manifest, configuring the rules if it exists
+    #[clap(short = 'm', long = "manifest", parse(from_os_str), default_value = MANIFEST)]
+    manifest: std::path::PathBuf,
+
+    /// Reports the violations of a rule as errors (repeatable)
+    #[clap(short = 'D', long = "deny")]
+    deny: Vec<String>,
+
+    /// Reports the violations of a rule as warnings (repeatable)
+    #[clap(short = 'W', long = "warn")]
+    warn: Vec<String>,
+
+    /// Does not check a rule (repeatable)
+    #[clap(short = 'A', long = "allow")]
+    allow: Vec<String>,
+
+    #[clap(flatten)]
+    load: LoadArgs,
+}
+
+/// Serves example responses for the relations of a program.
+#[derive(clap::Args, Debug)]
+struct MockArgs {
     /// The path to the source program
     #[clap(short = 'i', long = "input", parse(from_os_str))]
     input: std::path::PathBuf,
 
-    /// The path to the output OpenAPI description
+    /// The local port to listen on
+    #[clap(short = 'p', long = "port", default_value = "8080")]
+    port: u16,
+
+    #[clap(flatten)]
+    load: LoadArgs,
+}
+
+/// Infers the declaration of a schema from sample JSON payloads.
+#[derive(clap::Args, Debug)]
+struct InferSchemaArgs {
+    /// The paths to the sample JSON payloads
+    #[clap(parse(from_os_str), required = true)]
+    samples: Vec<std::path::PathBuf>,
+
+    /// The name of the declared reference
+    #[clap(short = 'n', long = "name", default_value = "@Schema")]
+    name: String,
+
+    /// The path to the output program, instead of the standard output
     #[clap(short = 'o', long = "output", parse(from_os_str))]
-    output: std::path::PathBuf,
+    output: Option<std::path::PathBuf>,
+}
+
+/// Checks recorded HTTP traffic against the relations of a program.
+#[derive(clap::Args, Debug)]
+struct VerifyTrafficArgs {
+    /// The path to the source program
+    #[clap(short = 'i', long = "input", parse(from_os_str))]
+    input: std::path::PathBuf,
+
+    /// The path to the HTTP archive (HAR) of the recorded traffic
+    #[clap(parse(from_os_str))]
+    har: std::path::PathBuf,
+
+    /// Reports the findings in JSON
+    #[clap(long = "json")]
+    json: bool,
+
+    #[clap(flatten)]
+    load: LoadArgs,
+}
+
+/// Compares two versions of a program and reports breaking changes.
+#[derive(clap::Args, Debug)]
+struct DiffArgs {
+    /// The path to the old version of the program
+    #[clap(parse(from_os_str))]
+    old: std::path::PathBuf,
+
+    /// The path to the new version of the program
+    #[clap(parse(from_os_str))]
+    new: std::path::PathBuf,
+
+    /// Reports the changes in JSON
+    #[clap(long = "json")]
+    json: bool,
+
+    #[clap(flatten)]
+    load: LoadArgs,
+}
+
+/// Prints the OpenAPI fragment of an expression or declaration of a program.
+#[derive(clap::Args, Debug)]
+struct EvalArgs {
+    /// The path to the program
+    #[clap(short = 'i', long = "input", parse(from_os_str))]
+    input: std::path::PathBuf,
+
+    /// The expression to evaluate in the environment of the program
+    #[clap(
+        long = "expr",
+        conflicts_with = "decl",
+        required_unless_present = "decl"
+    )]
+    expr: Option<String>,
+
+    /// The name of the declaration to evaluate
+    #[clap(long = "decl")]
+    decl: Option<String>,
+
+    #[clap(flatten)]
+    load: LoadArgs,
+}
+
+/// Evaluates declarations and expressions interactively.
+#[derive(clap::Args, Debug)]
+struct ReplArgs {
+    /// The path to a program to start from
+    #[clap(short = 'i', long = "input", parse(from_os_str))]
+    input: Option<std::path::PathBuf>,
+
+    #[clap(flatten)]
+    load: LoadArgs,
+}
 
+#[derive(clap::Args, Debug)]
+struct GenerateArgs {
     /// The path to a base OpenAPI description
     #[clap(short = 'b', long = "base", parse(from_os_str))]
     base: Option<std::path::PathBuf>,
+
+    /// The path to an OpenAPI Overlay to apply to the output (repeatable)
+    #[clap(long = "overlay", parse(from_os_str))]
+    overlays: Vec<std::path::PathBuf>,
+
+    /// Generates one OpenAPI description per module, or one JSON Schema per reference
+    #[clap(long = "split")]
+    split: bool,
+
+    /// Generates JSON Schema documents for the declared references instead of OpenAPI
+    #[clap(
+        long = "json-schema",
+        conflicts_with_all = &[
+            "base",
+            "overlays",
+            "dereference",
+            "source-map",
+            "source-extensions",
+            "examples"
+        ]
+    )]
+    json_schema: bool,
+
+    /// Generates a Rust crate with model types and a client trait in the output directory instead
+    #[clap(
+        long = "rust",
+        conflicts_with_all = &[
+            "json-schema",
+            "split",
+            "base",
+            "overlays",
+            "dereference",
+            "source-map",
+            "source-extensions",
+            "examples"
+        ]
+    )]
+    rust: bool,
+
+    /// Inlines all referenced schemas
+    #[clap(long = "dereference")]
+    dereference: bool,
+
+    /// Hoists repeated anonymous schemas into components
+    #[clap(long = "hoist")]
+    hoist: bool,
+
+    /// The number of nodes above which anonymous schemas are hoisted even if not repeated
+    #[clap(long = "hoist-size", requires = "hoist")]
+    hoist_size: Option<usize>,
+
+    /// Writes the source of each generated node in a sidecar file, named after the output
+    #[clap(long = "source-map")]
+    source_map: bool,
+
+    /// Adds the source of each generated node as an x-oal-source extension
+    #[clap(long = "source-extensions")]
+    source_extensions: bool,
+
+    /// Synthesises examples for media types, parameters and components which have none
+    #[clap(long = "examples")]
+    examples: bool,
+
+    /// The seed of the synthesised examples
+    #[clap(long = "seed", default_value = "0", requires = "examples")]
+    seed: u64,
 }
 
 /// Loads and parses a source file into a program.
-fn loader(l: &Locator) -> anyhow::Result<Program> {
+fn loader(resolvers: &Resolvers, l: &Locator) -> anyhow::Result<Program> {
     eprintln!("Loading module {}", l);
-    let path = l
-        .url
-        .to_file_path()
-        .map_err(|_| anyhow!("not a file path: {}", l))?;
-    let input = std::fs::read_to_string(path)?;
-    let program = oal_syntax::parse(input)?;
+    parse_module(resolvers, l)
+}
+
+/// Parses a source file into a program.
+///
+/// OpenAPI and JSON Schema documents are decompiled into a module declaring their schemas.
+fn parse_module(resolvers: &Resolvers, l: &Locator) -> anyhow::Result<Program> {
+    let input = resolvers.resolve(l)?;
+    let extension = std::path::Path::new(l.url.path()).extension();
+    let source = match extension.and_then(|e| e.to_str()) {
+        Some("yaml" | "yml" | "json") => {
+            let doc: serde_json::Value = serde_yaml::from_str(&input)?;
+            oal_codegen::decompile_module(doc)?
+        }
+        _ => input,
+    };
+    let program = oal_syntax::parse(source)?;
     Ok(program)
 }
 
+/// Returns the directories in which to look up imported modules.
+fn search_path(lib_paths: &[std::path::PathBuf]) -> anyhow::Result<Vec<Locator>> {
+    let env_paths = std::env::var_os("OAL_PATH")
+        .map(|p| std::env::split_paths(&p).collect::<Vec<_>>())
+        .unwrap_or_default();
+    lib_paths
+        .iter()
+        .chain(env_paths.iter())
+        .map(|p| Ok(Locator::from_dir(p)?))
+        .collect()
+}
+
+/// Parses the mapping of a URL scheme to a local directory.
+fn parse_scheme(s: &str) -> anyhow::Result<(String, std::path::PathBuf)> {
+    let (scheme, dir) = s
+        .split_once('=')
+        .ok_or_else(|| anyhow!("expected SCHEME=DIR: {}", s))?;
+    Ok((scheme.to_owned(), dir.into()))
+}
+
 /// Compiles a program.
 fn compiler(mods: &ModuleSet, l: &Locator, p: Program) -> anyhow::Result<Program> {
     eprintln!("Compiling module {}", l);
@@ -40,29 +406,719 @@ fn compiler(mods: &ModuleSet, l: &Locator, p: Program) -> anyhow::Result<Program
 fn main() -> anyhow::Result<()> {
     let args: Args = Args::parse();
 
-    let main_mod = Locator::try_from(args.input.as_path())?;
+    match args.command {
+        Some(Command::Import(import_args)) => import(import_args),
+        Some(Command::Build(build_args)) => build(build_args),
+        Some(Command::Watch(compile_args)) => watch(compile_args),
+        Some(Command::Repl(repl_args)) => repl::run(repl_args),
+        Some(Command::Eval(eval_args)) => eval(eval_args),
+        Some(Command::Check(check_args)) => check(check_args),
+        Some(Command::Diff(diff_args)) => diff(diff_args),
+        Some(Command::Lint(lint_args)) => lint(lint_args),
+        Some(Command::Mock(mock_args)) => mock(mock_args),
+        Some(Command::VerifyTraffic(traffic_args)) => verify_traffic(traffic_args),
+        Some(Command::InferSchema(infer_args)) => infer_schema(infer_args),
+        None => compile(args.compile),
+    }
+}
+
+/// Builds the targets of a project manifest.
+fn build(args: BuildArgs) -> anyhow::Result<()> {
+    let manifest = Manifest::read(&args.manifest)?;
+
+    for name in args.targets.iter() {
+        if !manifest.targets.iter().any(|t| t.name() == *name) {
+            return Err(anyhow!("unknown target: {}", name));
+        }
+    }
+
+    let targets: Vec<_> = manifest
+        .targets
+        .iter()
+        .filter(|t| args.targets.is_empty() || args.targets.contains(&t.name()))
+        .collect();
+
+    let entries = targets
+        .iter()
+        .map(|t| Locator::try_from(t.input.as_path()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let schemes: Vec<_> = manifest.schemes.clone().into_iter().collect();
+    let mods = load(&entries, &resolvers(&schemes), &manifest.lib_path, |_| ())?;
+
+    for (target, main) in targets.iter().zip(entries.iter()) {
+        eprintln!("Building target {}", target.name());
+        let options = target.options.clone();
+        let args = GenerateArgs {
+            base: options.base,
+            overlays: options.overlays.unwrap_or_default(),
+            split: options.split.unwrap_or_default(),
+            json_schema: options.format == Some(Format::JsonSchema),
+            rust: options.format == Some(Format::Rust),
+            dereference: options.dereference.unwrap_or_default(),
+            hoist: options.hoist.unwrap_or_default() || options.hoist_size.is_some(),
+            hoist_size: options.hoist_size,
+            source_map: options.source_map.unwrap_or_default(),
+            source_extensions: options.source_extensions.unwrap_or_default(),
+            examples: options.examples.unwrap_or_default() || options.seed.is_some(),
+            seed: options.seed.unwrap_or_default(),
+        };
+        generate(&mods, main, &target.output, &args)?;
+    }
+
+    Ok(())
+}
+
+/// Prints the OpenAPI fragment of an expression or declaration of a program.
+///
+/// Schema-like values give a schema, contents and ranges give responses,
+/// transfers give an operation and relations give a path item.
+/// The components used by the fragment follow as a second YAML document.
+fn eval(args: EvalArgs) -> anyhow::Result<()> {
+    let session = Session::new(Some(&args.input), args.load)?;
+
+    // A declaration, possibly imported, is evaluated as a variable.
+    let expr = match (args.expr, args.decl) {
+        (Some(expr), _) => expr,
+        (None, Some(decl)) => decl,
+        _ => unreachable!(),
+    };
+    let mods = session.eval(&expr)?;
+
+    let name = IT.into();
+    let (tag, _) = session.reduced(&mods, &name)?;
+    let (fragment, components) = session
+        .fragment(&mods, &name)?
+        .ok_or_else(|| anyhow!("no OpenAPI fragment for a value of type {}", tag))?;
+
+    print!("{}", serde_yaml::to_string(&fragment)?);
+    if !components.schemas.is_empty() {
+        println!();
+        print!(
+            "{}",
+            serde_yaml::to_string(&std::collections::BTreeMap::from([(
+                "components",
+                components
+            )]))?
+        );
+    }
+
+    Ok(())
+}
+
+/// Checks that programs compile, reporting the errors of each one.
+///
+/// Programs are loaded, compiled and exported to a specification, as when generating them,
+/// but separately so that the errors of one program do not hide those of the others.
+/// When validating, the OpenAPI description is also built with the base and overlays applied.
+fn check(args: CheckArgs) -> anyhow::Result<()> {
+    let entries = args
+        .inputs
+        .iter()
+        .map(|p| Locator::try_from(p.as_path()))
+        .collect::<Result<Vec<_>, _>>()?;
 
-    let mods = oal_compiler::load(&main_mod, loader, compiler)?;
+    let resolvers = resolvers(&args.load.schemes);
+    let base = args.base.as_deref().map(read_base).transpose()?;
+    let overlays = read_overlays(&args.overlays)?;
 
-    eprintln!("Generating API definition");
+    let mut failures = 0;
+    for main in entries.iter() {
+        eprintln!("Checking {}", main);
+        let result = load(
+            std::slice::from_ref(main),
+            &resolvers,
+            &args.load.lib_paths,
+            |_| (),
+        )
+        .and_then(|mods| Ok(oal_compiler::spec::Spec::from_entry(&mods, main)?))
+        .and_then(|spec| {
+            if !args.validate {
+                return Ok(());
+            }
+            let mut builder = oal_codegen::Builder::new().with_spec(spec);
+            if let Some(base) = &base {
+                builder = builder.with_base(base.clone());
+            }
+            let sources = builder.source_map();
+            let api = apply_overlays(builder.into_openapi(), &overlays)?;
+            // The description must read back as an OpenAPI document.
+            serde_json::from_value::<OpenAPI>(serde_json::to_value(&api)?)?;
+            validate_definition(&api, &sources)
+        });
+        if let Err(err) = result {
+            eprintln!("Error: {}", err);
+            failures += 1;
+        }
+    }
+
+    if failures > 0 {
+        Err(anyhow!("{} of {} programs failed", failures, entries.len()))
+    } else {
+        eprintln!("No errors found");
+        Ok(())
+    }
+}
+
+/// Compares two versions of a program, failing if any change is breaking.
+///
+/// Each version is loaded on its own, so that they can import different versions of a module.
+fn diff(args: DiffArgs) -> anyhow::Result<()> {
+    let resolvers = resolvers(&args.load.schemes);
+    let specs = [&args.old, &args.new]
+        .iter()
+        .map(|path| {
+            let main = Locator::try_from(path.as_path())?;
+            let mods = load(
+                std::slice::from_ref(&main),
+                &resolvers,
+                &args.load.lib_paths,
+                |_| (),
+            )?;
+            Ok(oal_compiler::spec::Spec::from_entry(&mods, &main)?)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let changes = oal_compiler::diff::diff(&specs[0], &specs[1]);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&changes)?);
+    } else {
+        for change in changes.iter() {
+            println!("{}", change);
+        }
+    }
+
+    let breaking = changes.iter().filter(|c| c.is_breaking()).count();
+    if breaking > 0 {
+        Err(anyhow!("breaking changes found: {}", breaking))
+    } else {
+        eprintln!("No breaking changes found");
+        Ok(())
+    }
+}
+
+/// Serves a program on localhost, answering each request with an example response.
+///
+/// Requests are validated against the parameters and the domain of the transfer first.
+/// The response is chosen with the `Prefer: status=CODE` request header, defaulting to success.
+fn mock(args: MockArgs) -> anyhow::Result<()> {
+    let main = Locator::try_from(args.input.as_path())?;
+    let resolvers = resolvers(&args.load.schemes);
+    let mods = load(
+        std::slice::from_ref(&main),
+        &resolvers,
+        &args.load.lib_paths,
+        |_| (),
+    )?;
+    let spec = oal_compiler::spec::Spec::from_entry(&mods, &main)?;
+    mock::serve(&mock::Mock::new(spec), args.port)
+}
+
+/// Checks each exchange of an HTTP archive against the program, failing if any does not conform.
+///
+/// Findings are undocumented endpoints, unexpected statuses and violations of the parameters,
+/// headers and bodies of the matching transfer.
+fn verify_traffic(args: VerifyTrafficArgs) -> anyhow::Result<()> {
+    let main = Locator::try_from(args.input.as_path())?;
+    let resolvers = resolvers(&args.load.schemes);
+    let mods = load(
+        std::slice::from_ref(&main),
+        &resolvers,
+        &args.load.lib_paths,
+        |_| (),
+    )?;
+    let spec = oal_compiler::spec::Spec::from_entry(&mods, &main)?;
+
+    let har: traffic::Har = serde_json::from_str(&std::fs::read_to_string(&args.har)?)?;
+    let findings = traffic::verify(&spec, &har);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&findings)?);
+    } else {
+        for finding in findings.iter() {
+            println!("{}", finding);
+        }
+    }
+
+    let count = |category| findings.iter().filter(|f| f.category == category).count();
+    if findings.is_empty() {
+        eprintln!("All {} recorded exchanges conform", har.len());
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "traffic does not conform: {} undocumented endpoints, {} unexpected statuses, {} violations",
+            count(traffic::Category::Undocumented),
+            count(traffic::Category::UnexpectedStatus),
+            count(traffic::Category::Violation),
+        ))
+    }
+}
+
+/// Checks programs against API design rules, failing if any rule is denied.
+///
+/// Rule levels are read from the `[lint]` section of the manifest if any,
+/// then overridden from the command line.
+fn lint(args: LintArgs) -> anyhow::Result<()> {
+    let manifest = if args.inputs.is_empty() || args.manifest.exists() {
+        Manifest::read(&args.manifest)?
+    } else {
+        Manifest::default()
+    };
+
+    let mut linter = oal_compiler::lint::Linter::new();
+    let cli_levels = [
+        (&args.allow, Level::Allow),
+        (&args.warn, Level::Warn),
+        (&args.deny, Level::Deny),
+    ];
+    let levels = manifest.lint.iter().map(|(id, level)| (id, *level)).chain(
+        cli_levels
+            .iter()
+            .flat_map(|(ids, level)| ids.iter().map(|id| (id, *level))),
+    );
+    for (id, level) in levels {
+        linter = linter.with_level(id, level)?;
+    }
+
+    let entries = if args.inputs.is_empty() {
+        manifest
+            .targets
+            .iter()
+            .map(|t| Locator::try_from(t.input.as_path()))
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        args.inputs
+            .iter()
+            .map(|p| Locator::try_from(p.as_path()))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+
+    let lib_paths: Vec<_> = manifest
+        .lib_path
+        .iter()
+        .chain(args.load.lib_paths.iter())
+        .cloned()
+        .collect();
+    let schemes: Vec<_> = manifest
+        .schemes
+        .clone()
+        .into_iter()
+        .chain(args.load.schemes.iter().cloned())
+        .collect();
+    let mods = load(&entries, &resolvers(&schemes), &lib_paths, |_| ())?;
+
+    let diags = linter.lint(&mods);
+    for diag in diags.iter() {
+        eprintln!("{}", diag);
+    }
+
+    let errors = diags.iter().filter(|d| d.level == Level::Deny).count();
+    let warnings = diags.len() - errors;
+    if errors > 0 {
+        Err(anyhow!("lint errors found: {}", errors))
+    } else {
+        eprintln!("Lint warnings found: {}", warnings);
+        Ok(())
+    }
+}
+
+/// Decompiles an OpenAPI description into a program.
+fn import(args: ImportArgs) -> anyhow::Result<()> {
+    eprintln!("Loading OpenAPI definition {}", args.input.display());
+
+    let file = std::fs::File::open(&args.input)?;
+    let mut api: OpenAPI = serde_yaml::from_reader(file)?;
 
-    let spec = oal_compiler::spec::Spec::try_from(&mods)?;
+    let program = oal_codegen::decompile(&api)?;
 
-    let mut builder = oal_codegen::Builder::new().with_spec(spec);
+    eprintln!("Writing program to {}", args.output.display());
+
+    std::fs::write(&args.output, program)?;
 
     if let Some(path) = args.base {
-        let file = std::fs::File::open(path)?;
-        let base = serde_yaml::from_reader(file)?;
-        builder = builder.with_base(base);
+        api.paths = Default::default();
+        api.components = None;
+        write_definition(&path, &api)?;
+    }
+
+    Ok(())
+}
+
+/// Infers the declaration of a reference from sample JSON payloads, merged together.
+fn infer_schema(args: InferSchemaArgs) -> anyhow::Result<()> {
+    let samples = args
+        .samples
+        .iter()
+        .map(|path| {
+            eprintln!("Loading sample {}", path.display());
+            let file = std::fs::File::open(path)?;
+            Ok(serde_json::from_reader(file)?)
+        })
+        .collect::<anyhow::Result<Vec<serde_json::Value>>>()?;
+
+    let (program, omitted) = oal_codegen::infer_declaration(&args.name, samples.iter())?;
+    for path in omitted.iter() {
+        eprintln!(
+            "Warning: property {} omitted, being null in all samples",
+            path
+        );
+    }
+
+    match args.output {
+        Some(path) => {
+            eprintln!("Writing program to {}", path.display());
+            std::fs::write(&path, program)?;
+        }
+        None => print!("{}", program),
+    }
+
+    Ok(())
+}
+
+/// Compiles programs into OpenAPI definitions, one per input.
+fn compile(args: CompileArgs) -> anyhow::Result<()> {
+    if args.inputs.len() != args.outputs.len() {
+        return Err(anyhow!("expected one output per input"));
+    }
+
+    let entries = args
+        .inputs
+        .iter()
+        .map(|p| Locator::try_from(p.as_path()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mods = load(
+        &entries,
+        &resolvers(&args.load.schemes),
+        &args.load.lib_paths,
+        |_| (),
+    )?;
+
+    for (main, output) in entries.iter().zip(args.outputs.iter()) {
+        generate(&mods, main, output, &args.generate)?;
+    }
+
+    Ok(())
+}
+
+/// Loads programs and their dependencies, sharing the modules they import.
+///
+/// The given function is called with each module before loading it.
+fn load<F: Fn(&Locator)>(
+    entries: &[Locator],
+    resolvers: &Resolvers,
+    lib_paths: &[std::path::PathBuf],
+    on_load: F,
+) -> anyhow::Result<ModuleSet> {
+    let search = search_path(lib_paths)?;
+
+    oal_compiler::load_all(
+        entries,
+        |from, path| Ok(resolvers.locate(from, path, &search)?),
+        |l| {
+            on_load(l);
+            loader(resolvers, l)
+        },
+        compiler,
+    )
+}
+
+/// Returns the module resolvers, with the given URL schemes mapped to local directories.
+fn resolvers(schemes: &[(String, std::path::PathBuf)]) -> Resolvers {
+    let mut resolvers = Resolvers::default();
+    for (scheme, dir) in schemes.iter() {
+        resolvers.register(scheme, DirResolver::new(dir));
+    }
+    resolvers
+}
+
+/// Returns the local files a build depends on, from the loaded modules and the given documents.
+fn watched_files<'a>(
+    loaded: impl IntoIterator<Item = &'a Locator>,
+    documents: impl IntoIterator<Item = &'a std::path::PathBuf>,
+) -> HashSet<std::path::PathBuf> {
+    loaded
+        .into_iter()
+        .filter_map(|l| l.url.to_file_path().ok())
+        .chain(documents.into_iter().cloned())
+        .map(|p| std::fs::canonicalize(&p).unwrap_or(p))
+        .collect()
+}
+
+/// Returns the directories to watch for changes to the given files.
+///
+/// Files are watched from their directory to survive editors replacing them.
+fn watched_dirs(files: &HashSet<std::path::PathBuf>) -> HashSet<std::path::PathBuf> {
+    files
+        .iter()
+        .filter_map(|p| p.parent().map(|d| d.to_path_buf()))
+        .collect()
+}
+
+/// Compiles programs again whenever one of the files they were built from changes.
+///
+/// The watched files are the modules loaded by the last build, successful or not,
+/// along with the base and overlay documents.
+fn watch(args: CompileArgs) -> anyhow::Result<()> {
+    if args.inputs.len() != args.outputs.len() {
+        return Err(anyhow!("expected one output per input"));
+    }
+
+    let entries = args
+        .inputs
+        .iter()
+        .map(|p| Locator::try_from(p.as_path()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::watcher(tx, std::time::Duration::from_millis(200))?;
+    let mut watched = HashSet::new();
+
+    loop {
+        let loaded = RefCell::new(HashSet::new());
+        let result = load(
+            &entries,
+            &resolvers(&args.load.schemes),
+            &args.load.lib_paths,
+            |l| {
+                loaded.borrow_mut().insert(l.clone());
+            },
+        )
+        .and_then(|mods| {
+            entries
+                .iter()
+                .zip(args.outputs.iter())
+                .try_for_each(|(main, output)| generate(&mods, main, output, &args.generate))
+        });
+        if let Err(err) = result {
+            eprintln!("Error: {:?}", err);
+        }
+
+        let files = watched_files(
+            loaded.into_inner().iter(),
+            args.generate.base.iter().chain(&args.generate.overlays),
+        );
+        let dirs = watched_dirs(&files);
+        // Errors, as for a directory removed in the meantime, are reported without ending the watch.
+        watched.retain(|dir| {
+            if dirs.contains(dir) {
+                return true;
+            }
+            if let Err(err) = watcher.unwatch(dir) {
+                eprintln!("Error: {}", err);
+            }
+            false
+        });
+        for dir in dirs {
+            if !watched.contains(&dir) {
+                match watcher.watch(&dir, notify::RecursiveMode::NonRecursive) {
+                    Ok(()) => {
+                        watched.insert(dir);
+                    }
+                    Err(err) => eprintln!("Error: {}", err),
+                }
+            }
+        }
+
+        eprintln!("Watching {} files for changes", files.len());
+
+        loop {
+            let changed = match rx.recv()? {
+                DebouncedEvent::Create(p)
+                | DebouncedEvent::Write(p)
+                | DebouncedEvent::Chmod(p)
+                | DebouncedEvent::Remove(p) => vec![p],
+                DebouncedEvent::Rename(from, to) => vec![from, to],
+                DebouncedEvent::Error(err, _) => {
+                    eprintln!("Error: {}", err);
+                    vec![]
+                }
+                _ => vec![],
+            };
+            if let Some(p) = changed.iter().find(|p| files.contains(*p)) {
+                eprintln!("Changed {}", p.display());
+                break;
+            }
+        }
+    }
+}
+
+/// Generates the definition of one of the loaded programs.
+fn generate(
+    mods: &ModuleSet,
+    main_mod: &Locator,
+    output: &std::path::Path,
+    args: &GenerateArgs,
+) -> anyhow::Result<()> {
+    eprintln!("Generating API definition for {}", main_mod);
+
+    let mut spec = oal_compiler::spec::Spec::from_entry(mods, main_mod)?;
+
+    if args.hoist {
+        let opts = Hoisting {
+            max_size: args.hoist_size,
+            ..Default::default()
+        };
+        hoist(&mut spec, &opts);
+    }
+
+    if args.rust {
+        let name = output
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        for (path, source) in oal_codegen::RustBuilder::new(spec).into_crate(&name) {
+            let path = output.join(path);
+            eprintln!("Writing Rust source to {}", path.display());
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(path, source)?;
+        }
+        return Ok(());
+    }
+
+    if args.json_schema {
+        let builder = oal_codegen::SchemaBuilder::new(spec);
+        if args.split {
+            for (name, schema) in builder.into_files() {
+                write_schema(&output.join(name), &schema)?;
+            }
+        } else {
+            write_schema(output, &builder.into_bundle())?;
+        }
+        return Ok(());
+    }
+
+    let mut builder = oal_codegen::Builder::new()
+        .with_spec(spec)
+        .with_dereference(args.dereference)
+        .with_examples(args.examples.then_some(args.seed));
+    let sources = builder.source_map();
+
+    if let Some(path) = &args.base {
+        builder = builder.with_base(read_base(path)?);
     }
 
-    let api = builder.into_openapi();
+    let overlays = read_overlays(&args.overlays)?;
 
-    let output = serde_yaml::to_string(&api)?;
+    if args.split {
+        for (loc, (path, api)) in builder.into_modules(main_mod)? {
+            let api = if loc == *main_mod {
+                apply_overlays(api, &overlays)?
+            } else {
+                api
+            };
+            write_output(&output.join(path), api, &sources, args)?;
+        }
+    } else {
+        let api = apply_overlays(builder.into_openapi(), &overlays)?;
+        write_output(output, api, &sources, args)?;
+    }
+
+    Ok(())
+}
+
+/// Reads a base OpenAPI description.
+fn read_base(path: &std::path::Path) -> anyhow::Result<OpenAPI> {
+    let file = std::fs::File::open(path)?;
+    let base = serde_yaml::from_reader(file)?;
+    Ok(base)
+}
+
+/// Reads OpenAPI Overlay documents, along with their path.
+fn read_overlays(
+    paths: &[std::path::PathBuf],
+) -> anyhow::Result<Vec<(&std::path::Path, oal_codegen::Overlay)>> {
+    paths
+        .iter()
+        .map(|path| {
+            let file = std::fs::File::open(path)?;
+            let overlay = serde_yaml::from_reader(file)?;
+            Ok((path.as_path(), overlay))
+        })
+        .collect()
+}
+
+/// Applies OpenAPI Overlay documents in order.
+fn apply_overlays(
+    mut api: OpenAPI,
+    overlays: &[(&std::path::Path, oal_codegen::Overlay)],
+) -> anyhow::Result<OpenAPI> {
+    for (path, overlay) in overlays.iter() {
+        eprintln!("Applying overlay {}", path.display());
+        api = overlay.apply(api)?;
+    }
+    Ok(api)
+}
+
+/// Checks a generated OpenAPI definition for structural errors, reporting each of them.
+fn validate_definition(api: &OpenAPI, sources: &SourceMap) -> anyhow::Result<()> {
+    let issues = oal_codegen::validate(api, sources);
+    for issue in issues.iter() {
+        eprintln!("Error: {}", issue);
+    }
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "invalid OpenAPI definition: {} errors",
+            issues.len()
+        ))
+    }
+}
+
+/// Validates and writes a generated OpenAPI definition, along with its sources if requested.
+fn write_output(
+    path: &std::path::Path,
+    api: OpenAPI,
+    sources: &SourceMap,
+    args: &GenerateArgs,
+) -> anyhow::Result<()> {
+    validate_definition(&api, sources)?;
+    let api = if args.source_extensions {
+        sources.annotate(api)?
+    } else {
+        api
+    };
+    write_definition(path, &api)?;
+    if args.source_map {
+        let mut sources = sources.clone();
+        sources.retain_nodes(&api)?;
+        let mut map_path = path.as_os_str().to_owned();
+        map_path.push(".map.json");
+        let map_path = std::path::PathBuf::from(map_path);
+        eprintln!("Writing source map to {}", map_path.display());
+        std::fs::write(map_path, serde_json::to_string_pretty(&sources)?)?;
+    }
+    Ok(())
+}
+
+/// Writes an OpenAPI definition in YAML.
+fn write_definition(path: &std::path::Path, api: &OpenAPI) -> anyhow::Result<()> {
+    let output = serde_yaml::to_string(api)?;
+
+    eprintln!("Writing OpenAPI definition to {}", path.display());
 
-    eprintln!("Writing OpenAPI definition to {}", args.output.display());
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, output)?;
+
+    Ok(())
+}
 
-    std::fs::write(args.output, output)?;
+/// Writes a JSON Schema document.
+fn write_schema(path: &std::path::Path, schema: &serde_json::Value) -> anyhow::Result<()> {
+    let output = serde_json::to_string_pretty(schema)?;
+
+    eprintln!("Writing JSON Schema to {}", path.display());
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, output)?;
 
     Ok(())
 }
@@ -0,0 +1,205 @@
+use anyhow::anyhow;
+use oal_compiler::lint::Level;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The name of the project manifest file.
+pub const MANIFEST: &str = "oal.toml";
+
+/// The kind of definition generated for a target.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Format {
+    Openapi,
+    JsonSchema,
+    Rust,
+}
+
+/// The build options of a target, each of which may be set project-wide.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Options {
+    pub base: Option<PathBuf>,
+    pub overlays: Option<Vec<PathBuf>>,
+    pub format: Option<Format>,
+    pub split: Option<bool>,
+    pub dereference: Option<bool>,
+    pub hoist: Option<bool>,
+    pub hoist_size: Option<usize>,
+    pub source_map: Option<bool>,
+    pub source_extensions: Option<bool>,
+    pub examples: Option<bool>,
+    pub seed: Option<u64>,
+}
+
+impl Options {
+    /// Returns the options of a target, falling back to the given defaults.
+    fn or(self, defaults: &Options) -> Options {
+        let defaults = defaults.clone();
+        Options {
+            base: self.base.or(defaults.base),
+            overlays: self.overlays.or(defaults.overlays),
+            format: self.format.or(defaults.format),
+            split: self.split.or(defaults.split),
+            dereference: self.dereference.or(defaults.dereference),
+            hoist: self.hoist.or(defaults.hoist),
+            hoist_size: self.hoist_size.or(defaults.hoist_size),
+            source_map: self.source_map.or(defaults.source_map),
+            source_extensions: self.source_extensions.or(defaults.source_extensions),
+            examples: self.examples.or(defaults.examples),
+            seed: self.seed.or(defaults.seed),
+        }
+    }
+
+    /// Checks that the options are supported by the format, as on the command line.
+    fn check(&self) -> anyhow::Result<()> {
+        let set = [
+            ("base", self.base.is_some()),
+            (
+                "overlays",
+                self.overlays.as_ref().is_some_and(|v| !v.is_empty()),
+            ),
+            ("split", self.split == Some(true)),
+            ("dereference", self.dereference == Some(true)),
+            ("source-map", self.source_map == Some(true)),
+            ("source-extensions", self.source_extensions == Some(true)),
+            (
+                "examples",
+                self.examples == Some(true) || self.seed.is_some(),
+            ),
+        ];
+        let unsupported: &[&str] = match self.format {
+            Some(Format::JsonSchema) => &[
+                "base",
+                "overlays",
+                "dereference",
+                "source-map",
+                "source-extensions",
+                "examples",
+            ],
+            Some(Format::Rust) => &[
+                "split",
+                "base",
+                "overlays",
+                "dereference",
+                "source-map",
+                "source-extensions",
+                "examples",
+            ],
+            _ => &[],
+        };
+        match set
+            .iter()
+            .find(|(name, is_set)| *is_set && unsupported.contains(name))
+        {
+            Some((name, _)) => Err(anyhow!("{} cannot be used with this format", name)),
+            None => Ok(()),
+        }
+    }
+
+    fn resolve(self, dir: &Path) -> Options {
+        Options {
+            base: self.base.map(|p| dir.join(p)),
+            overlays: self
+                .overlays
+                .map(|v| v.into_iter().map(|p| dir.join(p)).collect()),
+            ..self
+        }
+    }
+}
+
+/// An entry program and the definition generated from it.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct Target {
+    pub name: Option<String>,
+    pub input: PathBuf,
+    pub output: PathBuf,
+    #[serde(flatten)]
+    pub options: Options,
+    /// The keys of the target which are not options, to be reported
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+impl Target {
+    /// Returns the name of the target, or else its input as written in the manifest.
+    pub fn name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| self.input.display().to_string())
+    }
+}
+
+/// A project manifest.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Manifest {
+    /// The directories in which to look up imported modules
+    #[serde(default)]
+    pub lib_path: Vec<PathBuf>,
+    /// The local directory from which the modules of each URL scheme are resolved
+    #[serde(default)]
+    pub schemes: BTreeMap<String, PathBuf>,
+    /// The options applying to all targets unless overridden
+    #[serde(default)]
+    pub defaults: Options,
+    #[serde(default, rename = "target")]
+    pub targets: Vec<Target>,
+    /// The level of each lint rule, by rule identifier
+    #[serde(default)]
+    pub lint: BTreeMap<String, Level>,
+}
+
+impl Manifest {
+    /// Reads a manifest, resolving relative paths against its directory.
+    pub fn read(path: &Path) -> anyhow::Result<Manifest> {
+        let text = std::fs::read_to_string(path)?;
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        Manifest::parse(&text, dir)
+    }
+
+    /// Parses a manifest, resolving relative paths against the given directory.
+    ///
+    /// Unknown keys are rejected, as well as options not supported by the format of a target.
+    pub fn parse(text: &str, dir: &Path) -> anyhow::Result<Manifest> {
+        let manifest: Manifest = toml::from_str(text)?;
+        for target in manifest.targets.iter() {
+            let name = target.name();
+            if let Some(key) = target.unknown.keys().next() {
+                return Err(anyhow!("unknown key {} in target {}", key, name));
+            }
+            let options = target.options.clone().or(&manifest.defaults);
+            options
+                .check()
+                .map_err(|err| anyhow!("{} in target {}", err, name))?;
+        }
+        Ok(manifest.resolve(dir))
+    }
+
+    fn resolve(self, dir: &Path) -> Manifest {
+        let defaults = self.defaults;
+        Manifest {
+            lib_path: self.lib_path.into_iter().map(|p| dir.join(p)).collect(),
+            schemes: self
+                .schemes
+                .into_iter()
+                .map(|(scheme, p)| (scheme, dir.join(p)))
+                .collect(),
+            targets: self
+                .targets
+                .into_iter()
+                .map(|t| Target {
+                    name: Some(t.name()),
+                    input: dir.join(t.input),
+                    output: dir.join(t.output),
+                    options: t.options.or(&defaults).resolve(dir),
+                    ..t
+                })
+                .collect(),
+            defaults: Options::default(),
+            lint: self.lint,
+        }
+    }
+}
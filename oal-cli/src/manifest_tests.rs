@@ -0,0 +1,114 @@
+use crate::manifest::{Format, Manifest};
+use oal_compiler::lint::Level;
+use std::path::{Path, PathBuf};
+
+const MANIFEST: &str = r#"
+lib-path = ["lib"]
+
+[schemes]
+registry = "registry"
+shared = "/srv/shared"
+
+[defaults]
+base = "base.yaml"
+overlays = ["overlay.yaml"]
+hoist = true
+
+[[target]]
+name = "public"
+input = "main.oal"
+output = "openapi.yaml"
+hoist = false
+
+[[target]]
+input = "schemas.oal"
+output = "schemas.yaml"
+format = "openapi"
+base = "/abs/base.yaml"
+
+[lint]
+no-any = "deny"
+"#;
+
+#[test]
+fn manifest_paths() -> anyhow::Result<()> {
+    let manifest = Manifest::parse(MANIFEST, Path::new("/project"))?;
+
+    assert_eq!(manifest.lib_path, vec![PathBuf::from("/project/lib")]);
+    assert_eq!(
+        manifest.schemes.get("registry"),
+        Some(&PathBuf::from("/project/registry"))
+    );
+    assert_eq!(
+        manifest.schemes.get("shared"),
+        Some(&PathBuf::from("/srv/shared"))
+    );
+
+    let public = &manifest.targets[0];
+    assert_eq!(public.name(), "public");
+    assert_eq!(public.input, PathBuf::from("/project/main.oal"));
+    assert_eq!(public.output, PathBuf::from("/project/openapi.yaml"));
+
+    let schemas = &manifest.targets[1];
+    assert_eq!(schemas.name(), "schemas.oal");
+    assert_eq!(schemas.options.base, Some(PathBuf::from("/abs/base.yaml")));
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn manifest_defaults() -> anyhow::Result<()> {
+    let manifest = Manifest::parse(MANIFEST, Path::new("/project"))?;
+
+    let public = &manifest.targets[0].options;
+    assert_eq!(public.base, Some(PathBuf::from("/project/base.yaml")));
+    assert_eq!(
+        public.overlays,
+        Some(vec![PathBuf::from("/project/overlay.yaml")])
+    );
+    assert_eq!(public.hoist, Some(false));
+    assert_eq!(public.format, None);
+
+    let schemas = &manifest.targets[1].options;
+    assert_eq!(schemas.hoist, Some(true));
+    assert_eq!(schemas.format, Some(Format::Openapi));
+
+    assert_eq!(manifest.lint.get("no-any"), Some(&Level::Deny));
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn manifest_missing_output() {
+    assert!(Manifest::parse("[[target]]\ninput = \"a.oal\"\n", Path::new(".")).is_err());
+}
+
+#[test]
+fn manifest_unknown_keys() {
+    let target = "[[target]]\ninput = \"a.oal\"\noutput = \"a.yaml\"\ndereferenc = true\n";
+    let err = Manifest::parse(target, Path::new(".")).expect_err("expected error");
+    assert!(err.to_string().contains("dereferenc"));
+
+    let defaults = "[defaults]\nhoist-sise = 3\n";
+    assert!(Manifest::parse(defaults, Path::new(".")).is_err());
+
+    let root = "lib-paths = [\"lib\"]\n";
+    assert!(Manifest::parse(root, Path::new(".")).is_err());
+}
+
+#[test]
+fn manifest_format_conflicts() {
+    let rust = "[[target]]\ninput = \"a.oal\"\noutput = \"a\"\nformat = \"rust\"\nsplit = true\n";
+    let err = Manifest::parse(rust, Path::new(".")).expect_err("expected error");
+    assert_eq!(
+        err.to_string(),
+        "split cannot be used with this format in target a.oal"
+    );
+
+    let defaults = "[defaults]\nbase = \"base.yaml\"\n\n[[target]]\ninput = \"a.oal\"\noutput = \"a.json\"\nformat = \"json-schema\"\n";
+    assert!(Manifest::parse(defaults, Path::new(".")).is_err());
+
+    let openapi =
+        "[[target]]\ninput = \"a.oal\"\noutput = \"a\"\nsplit = true\nbase = \"b.yaml\"\n";
+    assert!(Manifest::parse(openapi, Path::new(".")).is_ok());
+}
@@ -0,0 +1,238 @@
+use anyhow::anyhow;
+use oal_codegen::Synthesizer;
+use oal_compiler::spec::{self, Spec};
+use oal_syntax::atom::{HttpStatus, HttpStatusRange, Method};
+use oal_validate::{route, Error, Kind, Validator};
+use serde_json::{json, Value};
+
+/// The request header choosing the status of the response, as in `Prefer: status=404`.
+const PREFER: &str = "prefer";
+
+/// A response of the mock server.
+pub struct Reply {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Reply {
+    fn error(status: u16, message: String) -> Self {
+        Reply {
+            status,
+            headers: vec![("Content-Type".to_owned(), "application/json".to_owned())],
+            body: json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+/// A server answering the requests to the relations of a specification with examples.
+pub struct Mock {
+    spec: Spec,
+}
+
+pub fn parse_method(m: &str) -> Option<Method> {
+    match m.to_ascii_uppercase().as_str() {
+        "GET" => Some(Method::Get),
+        "PUT" => Some(Method::Put),
+        "POST" => Some(Method::Post),
+        "PATCH" => Some(Method::Patch),
+        "DELETE" => Some(Method::Delete),
+        "OPTIONS" => Some(Method::Options),
+        "HEAD" => Some(Method::Head),
+        _ => None,
+    }
+}
+
+/// Returns the lowest status code of a range.
+fn range_code(range: HttpStatusRange) -> u16 {
+    match range {
+        HttpStatusRange::Info => 100,
+        HttpStatusRange::Success => 200,
+        HttpStatusRange::Redirect => 300,
+        HttpStatusRange::ClientError => 400,
+        HttpStatusRange::ServerError => 500,
+    }
+}
+
+/// Tells whether a status code belongs to the status of a content.
+fn has_status(status: &Option<HttpStatus>, code: u16) -> bool {
+    match status {
+        Some(HttpStatus::Code(c)) => c.get() == code,
+        Some(HttpStatus::Range(r)) => range_code(*r) / 100 == code / 100,
+        None => false,
+    }
+}
+
+fn is_success(status: &Option<HttpStatus>) -> bool {
+    match status {
+        Some(HttpStatus::Code(c)) => (200..300).contains(&c.get()),
+        Some(HttpStatus::Range(r)) => *r == HttpStatusRange::Success,
+        None => true,
+    }
+}
+
+impl Mock {
+    pub fn new(spec: Spec) -> Self {
+        Mock { spec }
+    }
+
+    /// Chooses the content of the response, by status and media type.
+    fn select<'a>(
+        &self,
+        ranges: &'a spec::Ranges,
+        prefer: Option<u16>,
+        accept: Option<&str>,
+    ) -> Option<(u16, &'a spec::Content)> {
+        let candidates: Vec<_> = match prefer {
+            Some(code) => ranges
+                .values()
+                .filter(|c| has_status(&c.status, code))
+                .map(|c| (code, c))
+                .collect(),
+            None => ranges
+                .values()
+                .filter(|c| is_success(&c.status))
+                .map(|c| {
+                    let code = match c.status {
+                        Some(HttpStatus::Code(code)) => code.get(),
+                        Some(HttpStatus::Range(r)) => range_code(r),
+                        None => 200,
+                    };
+                    (code, c)
+                })
+                .collect(),
+        };
+        let accepted = |(_, c): &&(u16, &spec::Content)| match (&c.media, accept) {
+            (Some(m), Some(a)) => a.contains(m.as_str()),
+            _ => false,
+        };
+        candidates
+            .iter()
+            .find(accepted)
+            .or_else(|| candidates.first())
+            .copied()
+    }
+
+    /// Answers a request with an example response, or with 400 if the request is invalid.
+    pub fn respond(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        body: &str,
+    ) -> Reply {
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+        };
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let found = match route(&self.spec, path) {
+            Some(found) => found,
+            None => return Reply::error(404, format!("no relation for {}", path)),
+        };
+        let xfer = match parse_method(method).and_then(|m| found.rel.xfers[m].as_ref()) {
+            Some(xfer) => xfer,
+            None => return Reply::error(405, format!("no {} transfer for {}", method, path)),
+        };
+
+        let validator = Validator::new(&self.spec.refs);
+        let path_values: Vec<_> = found
+            .vars
+            .iter()
+            .map(|(p, v)| (p.name.as_ref().to_owned(), v.to_string()))
+            .collect();
+        let path_params = found.vars.iter().map(|(p, _)| *p);
+        let query_values: Vec<_> = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+        let query_params = found
+            .rel
+            .uri
+            .params
+            .iter()
+            .chain(xfer.params.iter())
+            .flat_map(|o| o.props.iter());
+        let mut errors = validator.params(path_params, &path_values);
+        errors.extend(validator.params(query_params, &query_values));
+        // Request bodies are optional unless their schema is required.
+        match &xfer.domain.schema {
+            Some(schema) if body.trim().is_empty() && schema.required == Some(true) => {
+                errors.push(Error::new(Kind::Missing, "", "missing request body"))
+            }
+            Some(_) if body.trim().is_empty() => {}
+            Some(schema) => match serde_json::from_str(body) {
+                Ok(value) => errors.extend(validator.schema(schema, &value, "")),
+                Err(err) => return Reply::error(400, format!("invalid request body: {}", err)),
+            },
+            None => {}
+        }
+        if let Some(err) = errors.first() {
+            return Reply::error(400, err.to_string());
+        }
+
+        let prefer = header(PREFER)
+            .and_then(|p| p.split(';').find_map(|s| s.trim().strip_prefix("status=")))
+            .and_then(|s| s.parse().ok());
+        let (status, content) = match self.select(&xfer.ranges, prefer, header("accept")) {
+            Some(selected) => selected,
+            None => return Reply::error(501, "no such response".to_owned()),
+        };
+
+        let mut synth = Synthesizer::new(&self.spec.refs);
+        let mut headers = Vec::new();
+        for prop in content.headers.iter().flat_map(|o| o.props.iter()) {
+            let value = match synth.schema(&prop.schema) {
+                Value::String(s) => s,
+                v => v.to_string(),
+            };
+            headers.push((prop.name.as_ref().to_owned(), value));
+        }
+        let body = match &content.schema {
+            Some(schema) => {
+                let media = content.media.as_deref().unwrap_or("application/json");
+                headers.push(("Content-Type".to_owned(), media.to_owned()));
+                synth.schema(schema).to_string()
+            }
+            None => String::new(),
+        };
+        Reply {
+            status,
+            headers,
+            body,
+        }
+    }
+}
+
+/// Serves the mock API on localhost until interrupted.
+pub fn serve(mock: &Mock, port: u16) -> anyhow::Result<()> {
+    let server = tiny_http::Server::http(("127.0.0.1", port)).map_err(|err| anyhow!(err))?;
+    eprintln!("Serving mock API on http://127.0.0.1:{}", port);
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let reply = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => {
+                let headers: Vec<_> = request
+                    .headers()
+                    .iter()
+                    .map(|h| (h.field.to_string(), h.value.to_string()))
+                    .collect();
+                mock.respond(request.method().as_str(), request.url(), &headers, &body)
+            }
+            Err(err) => Reply::error(400, err.to_string()),
+        };
+        eprintln!("{} {} {}", request.method(), request.url(), reply.status);
+        let mut response =
+            tiny_http::Response::from_data(reply.body.into_bytes()).with_status_code(reply.status);
+        for (name, value) in reply.headers.iter() {
+            if let Ok(header) = tiny_http::Header::from_bytes(name.as_bytes(), value.as_bytes()) {
+                response.add_header(header);
+            }
+        }
+        if let Err(err) = request.respond(response) {
+            eprintln!("Error: {}", err);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use crate::mock::{Mock, Reply};
+use crate::tests::eval;
+use serde_json::Value;
+
+const CODE: &str = r#"
+    let @item = { 'id int `required: true`, 'name str `required: true` };
+    let errors = <status=404, {}> `description: "not found"`
+              :: <status=5XX, {}> `description: "failure"`;
+    let ok = <status=200, @item> `description: "ok"`;
+    res /items/{ 'id int } ( get -> ok :: errors, put : @item -> ok :: errors );
+    res /items/me ( get -> <status=200, str> `description: "me"` );
+    res /files/{ 'name str `pattern: "^[a-z ]+$"` } (
+      get -> <status=204,> `description: "none"`
+    );
+"#;
+
+fn mock() -> anyhow::Result<Mock> {
+    anyhow::Ok(Mock::new(eval(CODE)?))
+}
+
+fn request(mock: &Mock, method: &str, url: &str, prefer: Option<&str>, body: &str) -> Reply {
+    let headers: Vec<_> = prefer
+        .map(|p| ("Prefer".to_owned(), p.to_owned()))
+        .into_iter()
+        .collect();
+    mock.respond(method, url, &headers, body)
+}
+
+#[test]
+fn mock_routes() -> anyhow::Result<()> {
+    let mock = mock()?;
+
+    let reply = request(&mock, "GET", "/items/12", None, "");
+    assert_eq!(reply.status, 200);
+    let body: Value = serde_json::from_str(&reply.body)?;
+    assert!(body["id"].is_i64());
+    assert!(reply
+        .headers
+        .contains(&("Content-Type".to_owned(), "application/json".to_owned())));
+
+    let reply = request(&mock, "GET", "/items/me", None, "");
+    assert_eq!(reply.status, 200);
+    assert!(serde_json::from_str::<Value>(&reply.body)?.is_string());
+
+    assert_eq!(request(&mock, "GET", "/items/twelve", None, "").status, 400);
+    assert_eq!(
+        request(&mock, "GET", "/files/my%20file", None, "").status,
+        204
+    );
+    assert_eq!(
+        request(&mock, "GET", "/files/my%2Ffile", None, "").status,
+        400
+    );
+    assert_eq!(request(&mock, "GET", "/other", None, "").status, 404);
+    assert_eq!(request(&mock, "DELETE", "/items/12", None, "").status, 405);
+    assert_eq!(request(&mock, "BREW", "/items/12", None, "").status, 405);
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn mock_status() -> anyhow::Result<()> {
+    let mock = mock()?;
+
+    let reply = request(&mock, "GET", "/items/12", Some("status=404"), "");
+    assert_eq!(reply.status, 404);
+    assert_eq!(reply.body, "{}");
+
+    let reply = request(
+        &mock,
+        "GET",
+        "/items/12",
+        Some("return=minimal; status=503"),
+        "",
+    );
+    assert_eq!(reply.status, 503);
+
+    assert_eq!(
+        request(&mock, "GET", "/items/12", Some("status=201"), "").status,
+        501
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn mock_bodies() -> anyhow::Result<()> {
+    let mock = mock()?;
+
+    let valid = r#"{ "id": 1, "name": "a" }"#;
+    assert_eq!(request(&mock, "PUT", "/items/1", None, valid).status, 200);
+
+    let reply = request(&mock, "PUT", "/items/1", None, r#"{ "id": "one" }"#);
+    assert_eq!(reply.status, 400);
+    let error: Value = serde_json::from_str(&reply.body)?;
+    assert!(error["error"].is_string());
+
+    assert_eq!(request(&mock, "PUT", "/items/1", None, "{").status, 400);
+
+    // The request body is not required.
+    assert_eq!(request(&mock, "PUT", "/items/1", None, "").status, 200);
+
+    anyhow::Ok(())
+}
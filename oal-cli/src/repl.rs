@@ -0,0 +1,123 @@
+use crate::session::{Session, IT};
+use crate::ReplArgs;
+use oal_compiler::ModuleSet;
+use oal_syntax::ast::Statement;
+use oal_syntax::atom::Ident;
+use std::io::{BufRead, Write};
+
+const HELP: &str = "\
+Type a declaration, resource or import statement ending with ';' to add it to the session,
+or an expression to evaluate it. Commands:
+  :load <path>  imports a module into the session
+  :help         prints this message
+  :quit         ends the session";
+
+/// Evaluates a piece of input, printing the outcome.
+pub fn eval<W: Write>(session: &mut Session, input: &str, out: &mut W) -> anyhow::Result<()> {
+    if let Some(path) = input.strip_prefix(":load") {
+        declare(session, &format!("use \"{}\";", path.trim()), out)
+    } else if is_statement(input) {
+        declare(session, input, out)
+    } else {
+        let mods = session.eval(input)?;
+        describe(session, &mods, &IT.into(), out)
+    }
+}
+
+/// Adds statements to the session, describing the declared values.
+fn declare<W: Write>(session: &mut Session, stmts: &str, out: &mut W) -> anyhow::Result<()> {
+    let (source, mods) = session.compile(stmts)?;
+    let prg: oal_compiler::Program = oal_syntax::parse(stmts)?;
+    for stmt in prg.stmts.iter() {
+        if let Statement::Decl(decl) = stmt {
+            describe(session, &mods, &decl.name, out)?;
+        }
+    }
+    session.set_source(source);
+    Ok(())
+}
+
+/// Prints the tag, the reduced expression and the OpenAPI fragment of a declaration.
+fn describe<W: Write>(
+    session: &Session,
+    mods: &ModuleSet,
+    name: &Ident,
+    out: &mut W,
+) -> anyhow::Result<()> {
+    let (tag, expr) = session.reduced(mods, name)?;
+    writeln!(out, "{} : {}", name, tag)?;
+    writeln!(out, "{}", expr)?;
+
+    if let Some((fragment, components)) = session.fragment(mods, name)? {
+        write!(out, "{}", serde_yaml::to_string(&fragment)?)?;
+        if !components.schemas.is_empty() {
+            let schemas = serde_yaml::to_string(&components.schemas)?;
+            write!(
+                out,
+                "\n# components\n{}",
+                schemas.trim_start_matches("---\n")
+            )?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Tells whether the input is made of statements rather than an expression.
+pub fn is_statement(input: &str) -> bool {
+    let input = input.trim_start();
+    input.starts_with('#')
+        || ["let", "res", "use"].iter().any(|kw| {
+            input
+                .strip_prefix(kw)
+                .and_then(|rest| rest.chars().next())
+                .is_some_and(char::is_whitespace)
+        })
+}
+
+/// Runs an interactive session on the standard input, starting from a program if any.
+pub fn run(args: ReplArgs) -> anyhow::Result<()> {
+    let mut session = Session::new(args.input.as_deref(), args.load)?;
+    session.compile("")?;
+
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+
+    eprintln!("Type :help for help.");
+
+    loop {
+        write!(out, "oal> ")?;
+        out.flush()?;
+        let mut input = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        // Statements may span several lines up to the closing semicolon.
+        while is_statement(&input) && !input.trim_end().ends_with(';') {
+            write!(out, "...> ")?;
+            out.flush()?;
+            match lines.next() {
+                Some(line) => {
+                    input.push('\n');
+                    input.push_str(&line?);
+                }
+                None => break,
+            }
+        }
+        match input.trim() {
+            "" => {}
+            ":quit" | ":q" => break,
+            ":help" => writeln!(out, "{}", HELP)?,
+            input => {
+                if let Err(err) = eval(&mut session, input, &mut out) {
+                    writeln!(out, "Error: {}", err)?;
+                }
+            }
+        }
+    }
+
+    writeln!(out)?;
+    Ok(())
+}
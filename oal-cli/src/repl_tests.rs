@@ -0,0 +1,59 @@
+use crate::repl::{eval, is_statement};
+use crate::session::Session;
+use crate::LoadArgs;
+
+fn session() -> anyhow::Result<Session> {
+    let args = LoadArgs {
+        schemes: Vec::new(),
+        lib_paths: Vec::new(),
+    };
+    Session::new(None, args)
+}
+
+fn run(session: &mut Session, input: &str) -> anyhow::Result<String> {
+    let mut out = Vec::new();
+    eval(session, input, &mut out)?;
+    Ok(String::from_utf8(out)?)
+}
+
+#[test]
+fn repl_statements() {
+    assert!(is_statement("let a = num;"));
+    assert!(is_statement("  res / ( get -> {} );"));
+    assert!(is_statement("use \"module.oal\";"));
+    assert!(is_statement("# description: \"a\"\nlet a = num;"));
+    assert!(!is_statement("letter"));
+    assert!(!is_statement("let"));
+    assert!(!is_statement("{ 'a num }"));
+}
+
+#[test]
+fn repl_declare() -> anyhow::Result<()> {
+    let mut session = session()?;
+
+    let out = run(&mut session, "let a = num;")?;
+    assert!(out.starts_with("a : "));
+
+    let out = run(&mut session, "[a]")?;
+    assert!(out.starts_with("it : "));
+    assert!(out.contains("type: array"));
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn repl_rollback() -> anyhow::Result<()> {
+    let mut session = session()?;
+    run(&mut session, "let a = num;")?;
+
+    // A failing statement leaves the session as it was.
+    assert!(run(&mut session, "let b = a & str;").is_err());
+    assert!(run(&mut session, "b").is_err());
+    assert!(run(&mut session, "let c = ;").is_err());
+
+    let out = run(&mut session, "let b = [a];")?;
+    assert!(out.starts_with("b : "));
+    assert!(run(&mut session, "b").is_ok());
+
+    anyhow::Ok(())
+}
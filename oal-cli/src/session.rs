@@ -0,0 +1,99 @@
+use crate::{parse_module, resolvers, search_path, LoadArgs};
+use oal_codegen::Fragment;
+use oal_compiler::resolver::{FileResolver, Resolver};
+use oal_compiler::spec::Spec;
+use oal_compiler::{Locator, ModuleSet, Tag, Tagged};
+use oal_syntax::ast::Statement;
+use oal_syntax::atom::Ident;
+use openapiv3::Components;
+
+/// The name under which expressions are evaluated.
+pub const IT: &str = "it";
+
+/// A program held in memory, to which statements can be added.
+pub struct Session {
+    pub main: Locator,
+    source: String,
+    args: LoadArgs,
+}
+
+impl Session {
+    /// Starts a session from a program if any, or from an empty one in the current directory.
+    pub fn new(input: Option<&std::path::Path>, args: LoadArgs) -> anyhow::Result<Session> {
+        let (main, source) = match input {
+            Some(path) => (Locator::try_from(path)?, std::fs::read_to_string(path)?),
+            None => (
+                Locator::from_dir(std::path::Path::new("."))?.join("repl.oal")?,
+                String::new(),
+            ),
+        };
+        Ok(Session { main, source, args })
+    }
+
+    /// Loads and compiles the session program extended with the given statements.
+    ///
+    /// Returns the extended source along with the module set.
+    pub fn compile(&self, stmts: &str) -> anyhow::Result<(String, ModuleSet)> {
+        let source = format!("{}\n{}", self.source, stmts);
+        let main = self.main.clone();
+        let session = source.clone();
+        let mut resolvers = resolvers(&self.args.schemes);
+        resolvers.register("file", move |l: &Locator| {
+            if *l == main {
+                Ok(session.clone())
+            } else {
+                FileResolver.resolve(l)
+            }
+        });
+        let search = search_path(&self.args.lib_paths)?;
+        let mods = oal_compiler::load_with(
+            &self.main,
+            |from, path| Ok(resolvers.locate(from, path, &search)?),
+            |l| parse_module(&resolvers, l),
+            |mods, l, p| Ok(oal_compiler::compile(mods, l, p)?),
+        )?;
+        Ok((source, mods))
+    }
+
+    /// Compiles the session program with an expression declared as [`IT`].
+    pub fn eval(&self, expr: &str) -> anyhow::Result<ModuleSet> {
+        let (_, mods) = self.compile(&format!("let {} = {};", IT, expr))?;
+        Ok(mods)
+    }
+
+    /// Replaces the session program.
+    pub fn set_source(&mut self, source: String) {
+        self.source = source;
+    }
+
+    /// Returns the inferred tag and the reduced expression of a declaration of the session.
+    pub fn reduced(&self, mods: &ModuleSet, name: &Ident) -> anyhow::Result<(Tag, String)> {
+        mods.main()
+            .stmts
+            .iter()
+            .rev()
+            .find_map(|s| match s {
+                Statement::Decl(d) if d.name == *name => {
+                    Some((d.expr.unwrap_tag(), oal_syntax::print::print(&d.expr)))
+                }
+                _ => None,
+            })
+            .ok_or_else(|| anyhow::anyhow!("unknown declaration: {}", name))
+    }
+
+    /// Generates the OpenAPI fragment of a declaration, along with the components it uses.
+    pub fn fragment(
+        &self,
+        mods: &ModuleSet,
+        name: &Ident,
+    ) -> anyhow::Result<Option<(Fragment, Components)>> {
+        let (tag, _) = self.reduced(mods, name)?;
+        if tag.is_schema_like() || matches!(tag, Tag::Property | Tag::Transfer) {
+            let (fragment, spec) = Spec::from_decl(mods, &self.main, name)?;
+            let builder = oal_codegen::Builder::new().with_spec(spec);
+            Ok(Some(builder.into_fragment(&fragment)))
+        } else {
+            Ok(None)
+        }
+    }
+}
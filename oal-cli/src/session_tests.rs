@@ -0,0 +1,60 @@
+use crate::session::Session;
+use crate::LoadArgs;
+use oal_compiler::Tag;
+
+fn session() -> anyhow::Result<Session> {
+    let args = LoadArgs {
+        schemes: Vec::new(),
+        lib_paths: Vec::new(),
+    };
+    Session::new(None, args)
+}
+
+#[test]
+fn session_compile() -> anyhow::Result<()> {
+    let mut session = session()?;
+
+    let (source, mods) = session.compile("let a = num;")?;
+    let (tag, expr) = session.reduced(&mods, &"a".into())?;
+    assert_eq!(tag, Tag::Primitive);
+    assert_eq!(expr, "num");
+
+    // Compiling does not extend the session until the source is set.
+    let (_, mods) = session.compile("")?;
+    assert!(session.reduced(&mods, &"a".into()).is_err());
+
+    session.set_source(source);
+    let (_, mods) = session.compile("let b = [a];")?;
+    assert!(session.reduced(&mods, &"a".into()).is_ok());
+    assert!(session.reduced(&mods, &"b".into()).is_ok());
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn session_eval() -> anyhow::Result<()> {
+    let mut session = session()?;
+    let (source, _) = session.compile("let @a = { 'x num };")?;
+    session.set_source(source);
+
+    let mods = session.eval("[@a]")?;
+    let (tag, _) = session.reduced(&mods, &crate::session::IT.into())?;
+    assert_eq!(tag, Tag::Array);
+
+    let fragment = session.fragment(&mods, &crate::session::IT.into())?;
+    let (_, components) = fragment.expect("expected a fragment");
+    assert!(components.schemas.contains_key("a"));
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn session_errors() -> anyhow::Result<()> {
+    let session = session()?;
+
+    assert!(session.compile("let a = ;").is_err());
+    assert!(session.compile("let a = b;").is_err());
+    assert!(session.eval("num & str").is_err());
+
+    anyhow::Ok(())
+}
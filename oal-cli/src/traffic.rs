@@ -0,0 +1,252 @@
+use crate::mock::parse_method;
+use oal_compiler::spec::{Content, Object, Ranges, Spec};
+use oal_validate::{route, Error, Kind, Validator};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+
+/// An HTTP archive (HAR), as recorded by browsers and proxies.
+#[derive(Deserialize, Debug)]
+pub struct Har {
+    log: Log,
+}
+
+#[derive(Deserialize, Debug)]
+struct Log {
+    entries: Vec<Entry>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Entry {
+    request: Request,
+    response: Response,
+}
+
+#[derive(Deserialize, Debug)]
+struct NameValue {
+    name: String,
+    value: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Request {
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: Vec<NameValue>,
+    post_data: Option<Body>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Response {
+    status: u16,
+    #[serde(default)]
+    headers: Vec<NameValue>,
+    content: Body,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Body {
+    #[serde(default)]
+    mime_type: String,
+    text: Option<String>,
+    encoding: Option<String>,
+}
+
+impl Har {
+    /// Returns the number of recorded exchanges.
+    pub fn len(&self) -> usize {
+        self.log.entries.len()
+    }
+}
+
+#[derive(Serialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Category {
+    /// No relation or transfer matches the request
+    Undocumented,
+    /// The transfer has no response for the recorded status
+    UnexpectedStatus,
+    /// A part of the exchange does not match its specification
+    Violation,
+}
+
+/// A mismatch between a recorded exchange and the program.
+#[derive(Serialize, Debug)]
+pub struct Finding {
+    pub category: Category,
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    /// The part of the exchange, like `query` or `response body`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part: Option<&'static str>,
+    /// The JSON pointer to the offending value within the part
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub pointer: String,
+    pub message: String,
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{} {} {}: ", self.method, self.url, self.status)?;
+        match self.part {
+            Some(part) if self.pointer.is_empty() => write!(f, "{}: {}", part, self.message),
+            Some(part) => write!(f, "{} {}: {}", part, self.pointer, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Returns the recorded values of the headers declared by an object, by declared name.
+fn header_values(decl: Option<&Object>, recorded: &[NameValue]) -> Vec<(String, String)> {
+    decl.iter()
+        .flat_map(|o| o.props.iter())
+        .filter_map(|p| {
+            let name = p.name.as_ref();
+            recorded
+                .iter()
+                .find(|h| h.name.eq_ignore_ascii_case(name))
+                .map(|h| (name.to_owned(), h.value.clone()))
+        })
+        .collect()
+}
+
+/// Selects the response content for a status, preferring the recorded media type.
+fn response<'a>(ranges: &'a Ranges, status: u16, mime: &str) -> Option<&'a Content> {
+    let selected = oal_validate::response(ranges, status)?;
+    let same_media = ranges.values().find(|c| {
+        c.status == selected.status && matches!(&c.media, Some(m) if mime.starts_with(m.as_str()))
+    });
+    same_media.or(Some(selected))
+}
+
+/// Checks the media type and the body of a request or a response.
+///
+/// Only JSON bodies are validated against the schema, and bodies omitted from the archive are skipped.
+fn body(validator: &Validator, content: &Content, body: &Body) -> Vec<Error> {
+    let mut errors = Vec::new();
+    let text = body.text.as_deref().filter(|t| !t.is_empty());
+    if let Some(media) = content.media.as_deref() {
+        if text.is_some() && !body.mime_type.starts_with(media) {
+            let msg = format!("expected media type {}", media);
+            errors.push(Error::new(Kind::InvalidType, "", msg));
+        }
+    }
+    let json = body.mime_type.is_empty() || body.mime_type.contains("json");
+    match (&content.schema, text) {
+        (Some(schema), Some(text)) if json && body.encoding.is_none() => {
+            match serde_json::from_str::<Value>(text) {
+                Ok(value) => errors.extend(validator.schema(schema, &value, "")),
+                Err(err) => errors.push(Error::new(Kind::InvalidType, "", err.to_string())),
+            }
+        }
+        (None, Some(_)) => errors.push(Error::new(Kind::InvalidType, "", "expected no body")),
+        _ => {}
+    }
+    errors
+}
+
+/// Checks a recorded exchange against the relation and transfer matching its request.
+fn exchange(spec: &Spec, validator: &Validator, entry: &Entry) -> Vec<Finding> {
+    let (req, res) = (&entry.request, &entry.response);
+    let finding = |category, part, err: Error| Finding {
+        category,
+        method: req.method.clone(),
+        url: req.url.clone(),
+        status: res.status,
+        part,
+        pointer: err.pointer,
+        message: err.message,
+    };
+    let undocumented = |kind, msg: &str| {
+        vec![finding(
+            Category::Undocumented,
+            None,
+            Error::new(kind, "", msg),
+        )]
+    };
+
+    let url = match url::Url::parse(&req.url) {
+        Ok(url) => url,
+        Err(err) => return undocumented(Kind::NoRelation, &format!("invalid URL: {}", err)),
+    };
+    let found = match route(spec, url.path()) {
+        Some(found) => found,
+        None => return undocumented(Kind::NoRelation, "undocumented endpoint"),
+    };
+    let xfer = match parse_method(&req.method).and_then(|m| found.rel.xfers[m].as_ref()) {
+        Some(xfer) => xfer,
+        None => return undocumented(Kind::NoTransfer, "undocumented method"),
+    };
+
+    let mut parts: Vec<(&'static str, Vec<Error>)> = Vec::new();
+
+    let path_values: Vec<_> = found
+        .vars
+        .iter()
+        .map(|(p, v)| (p.name.as_ref().to_owned(), v.to_string()))
+        .collect();
+    let path_params = found.vars.iter().map(|(p, _)| *p);
+    parts.push(("path", validator.params(path_params, &path_values)));
+
+    let query_values: Vec<_> = url.query_pairs().into_owned().collect();
+    let query_params = found
+        .rel
+        .uri
+        .params
+        .iter()
+        .chain(xfer.params.iter())
+        .flat_map(|o| o.props.iter());
+    parts.push(("query", validator.params(query_params, &query_values)));
+
+    let req_headers = xfer.domain.headers.as_ref();
+    let values = header_values(req_headers, &req.headers);
+    let props = req_headers.iter().flat_map(|o| o.props.iter());
+    parts.push(("request headers", validator.params(props, &values)));
+
+    if let Some(data) = &req.post_data {
+        parts.push(("request body", body(validator, &xfer.domain, data)));
+    }
+
+    // A null status is recorded for aborted requests, with no response to check.
+    if res.status != 0 {
+        match response(&xfer.ranges, res.status, &res.content.mime_type) {
+            Some(content) => {
+                let res_headers = content.headers.as_ref();
+                let values = header_values(res_headers, &res.headers);
+                let props = res_headers.iter().flat_map(|o| o.props.iter());
+                parts.push(("response headers", validator.params(props, &values)));
+                parts.push(("response body", body(validator, content, &res.content)));
+            }
+            None => {
+                let msg = format!("undocumented status {}", res.status);
+                parts.push(("status", vec![Error::new(Kind::UnexpectedStatus, "", msg)]));
+            }
+        }
+    }
+
+    let mut findings = Vec::new();
+    for (part, errors) in parts {
+        for err in errors {
+            let category = match err.kind {
+                Kind::UnexpectedStatus => Category::UnexpectedStatus,
+                _ => Category::Violation,
+            };
+            findings.push(finding(category, Some(part), err));
+        }
+    }
+    findings
+}
+
+/// Checks every recorded exchange of an archive against a specification.
+pub fn verify(spec: &Spec, har: &Har) -> Vec<Finding> {
+    let validator = Validator::new(&spec.refs);
+    har.log
+        .entries
+        .iter()
+        .flat_map(|entry| exchange(spec, &validator, entry))
+        .collect()
+}
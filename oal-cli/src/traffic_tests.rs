@@ -0,0 +1,134 @@
+use crate::tests::eval;
+use crate::traffic::{verify, Category, Har};
+use serde_json::{json, Value};
+
+const CODE: &str = r#"
+    let @item = { 'id int `required: true`, 'name str };
+    let ok = <status=200, @item> `description: "ok"`;
+    let missing = <status=404, {}> `description: "not found"`;
+    res /items/{ 'id int } (
+      get { 'limit int } -> ok :: missing,
+      put : @item -> ok
+    );
+"#;
+
+fn entry(method: &str, url: &str, body: Option<Value>, status: u16, response: Value) -> Value {
+    let post_data = body.map(|b| json!({ "mimeType": "application/json", "text": b.to_string() }));
+    json!({
+        "request": { "method": method, "url": url, "headers": [], "postData": post_data },
+        "response": {
+            "status": status,
+            "headers": [],
+            "content": { "mimeType": "application/json", "text": response.to_string() }
+        }
+    })
+}
+
+fn har(entries: Vec<Value>) -> anyhow::Result<Har> {
+    anyhow::Ok(serde_json::from_value(
+        json!({ "log": { "entries": entries } }),
+    )?)
+}
+
+#[test]
+fn traffic_conforming() -> anyhow::Result<()> {
+    let item = json!({ "id": 1, "name": "a" });
+    let har = har(vec![
+        entry("GET", "http://api/items/1?limit=2", None, 200, item.clone()),
+        entry("GET", "http://api/items/1", None, 404, json!({})),
+        entry("PUT", "http://api/items/1", Some(item.clone()), 200, item),
+        entry("GET", "http://api/items/1", None, 0, Value::Null),
+    ])?;
+
+    assert_eq!(har.len(), 4);
+    assert!(verify(&eval(CODE)?, &har).is_empty());
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn traffic_mismatching() -> anyhow::Result<()> {
+    let item = json!({ "id": 1, "name": "a" });
+    let har = har(vec![
+        entry("GET", "http://api/other", None, 200, item.clone()),
+        entry("DELETE", "http://api/items/1", None, 200, item.clone()),
+        entry("GET", "http://api/items/one", None, 200, item.clone()),
+        entry(
+            "GET",
+            "http://api/items/1?limit=all",
+            None,
+            200,
+            item.clone(),
+        ),
+        entry("GET", "http://api/items/1", None, 418, item.clone()),
+        entry(
+            "PUT",
+            "http://api/items/1",
+            Some(json!({ "id": "1" })),
+            200,
+            item,
+        ),
+        entry("GET", "http://api/items/1", None, 200, json!({ "name": 2 })),
+    ])?;
+
+    let findings: Vec<_> = verify(&eval(CODE)?, &har)
+        .into_iter()
+        .map(|f| (f.category, f.part, f.pointer, f.url))
+        .collect();
+
+    assert_eq!(
+        findings,
+        vec![
+            (
+                Category::Undocumented,
+                None,
+                "".to_owned(),
+                "http://api/other".to_owned()
+            ),
+            (
+                Category::Undocumented,
+                None,
+                "".to_owned(),
+                "http://api/items/1".to_owned()
+            ),
+            (
+                Category::Violation,
+                Some("path"),
+                "/id".to_owned(),
+                "http://api/items/one".to_owned()
+            ),
+            (
+                Category::Violation,
+                Some("query"),
+                "/limit".to_owned(),
+                "http://api/items/1?limit=all".to_owned()
+            ),
+            (
+                Category::UnexpectedStatus,
+                Some("status"),
+                "".to_owned(),
+                "http://api/items/1".to_owned()
+            ),
+            (
+                Category::Violation,
+                Some("request body"),
+                "/id".to_owned(),
+                "http://api/items/1".to_owned()
+            ),
+            (
+                Category::Violation,
+                Some("response body"),
+                "/id".to_owned(),
+                "http://api/items/1".to_owned()
+            ),
+            (
+                Category::Violation,
+                Some("response body"),
+                "/name".to_owned(),
+                "http://api/items/1".to_owned()
+            ),
+        ]
+    );
+
+    anyhow::Ok(())
+}
@@ -0,0 +1,68 @@
+use crate::{load, resolvers, watched_dirs, watched_files};
+use oal_compiler::Locator;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Loads a program as watch does, returning the directories to watch.
+fn dirs(main: &Path) -> anyhow::Result<HashSet<PathBuf>> {
+    let entries = [Locator::try_from(main)?];
+    let loaded = RefCell::new(HashSet::new());
+    load(&entries, &resolvers(&[]), &[], |l| {
+        loaded.borrow_mut().insert(l.clone());
+    })?;
+    let files = watched_files(loaded.into_inner().iter(), &[]);
+    anyhow::Ok(watched_dirs(&files))
+}
+
+#[test]
+fn watch_imported_dirs() -> anyhow::Result<()> {
+    let root = std::env::temp_dir().join(format!("oal-watch-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("sub"))?;
+    std::fs::create_dir_all(root.join("other"))?;
+    let root = std::fs::canonicalize(root)?;
+    let main = root.join("main.oal");
+    std::fs::write(root.join("sub/b.oal"), "let @b = {};")?;
+    std::fs::write(root.join("other/c.oal"), "let @b = { 'x str };")?;
+
+    std::fs::write(&main, r#"use "sub/b.oal"; res / ( get -> @b );"#)?;
+    let before = dirs(&main)?;
+
+    assert_eq!(before, HashSet::from([root.clone(), root.join("sub")]));
+
+    std::fs::write(&main, r#"use "other/c.oal"; res / ( get -> @b );"#)?;
+    let after = dirs(&main)?;
+
+    assert_eq!(
+        before.difference(&after).collect::<Vec<_>>(),
+        vec![&root.join("sub")]
+    );
+    assert_eq!(
+        after.difference(&before).collect::<Vec<_>>(),
+        vec![&root.join("other")]
+    );
+
+    std::fs::remove_dir_all(&root)?;
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn watch_documents() -> anyhow::Result<()> {
+    let module = Locator::try_from("file:///p/api/main.oal")?;
+    let remote = Locator::try_from("https://example.com/common.oal")?;
+    let base = PathBuf::from("/p/docs/base.yaml");
+
+    let files = watched_files([&module, &remote], [&base]);
+
+    assert_eq!(
+        files,
+        HashSet::from([PathBuf::from("/p/api/main.oal"), base.clone()])
+    );
+    assert_eq!(
+        watched_dirs(&files),
+        HashSet::from([PathBuf::from("/p/api"), PathBuf::from("/p/docs")])
+    );
+
+    anyhow::Ok(())
+}
@@ -0,0 +1,207 @@
+use crate::tests::eval;
+use crate::Builder;
+use oal_compiler::spec::{Object, Property, Reference, Schema, SchemaExpr};
+use openapiv3::{OpenAPI, ReferenceOr, SchemaKind, Type};
+
+fn response_schema(api: &OpenAPI) -> &ReferenceOr<openapiv3::Schema> {
+    let op = api.paths.paths["/"]
+        .as_item()
+        .and_then(|p| p.get.as_ref())
+        .expect("expected get operation");
+    let res = op
+        .responses
+        .default
+        .as_ref()
+        .and_then(ReferenceOr::as_item)
+        .expect("expected default response");
+    res.content["application/json"]
+        .schema
+        .as_ref()
+        .expect("expected schema")
+}
+
+#[test]
+fn builder_reference() -> anyhow::Result<()> {
+    let spec = eval("let @b = {}; let @a = { 'b @b }; res / ( get -> @a );")?;
+
+    let api = Builder::new().with_spec(spec).into_openapi();
+
+    assert!(matches!(
+        response_schema(&api),
+        ReferenceOr::Reference { reference } if reference == "#/components/schemas/a"
+    ));
+    assert_eq!(
+        api.components.expect("expected components").schemas.len(),
+        2
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn builder_dereference() -> anyhow::Result<()> {
+    let spec = eval("let @b = {}; let @a = { 'b @b }; res / ( get -> @a );")?;
+
+    let api = Builder::new()
+        .with_spec(spec)
+        .with_dereference(true)
+        .into_openapi();
+
+    match response_schema(&api) {
+        ReferenceOr::Item(s) => match &s.schema_kind {
+            SchemaKind::Type(Type::Object(o)) => {
+                assert!(matches!(o.properties["b"], ReferenceOr::Item(_)))
+            }
+            _ => panic!("expected object schema"),
+        },
+        _ => panic!("expected inline schema"),
+    }
+    assert!(api
+        .components
+        .expect("expected components")
+        .schemas
+        .is_empty());
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn builder_dereference_recursive() -> anyhow::Result<()> {
+    let mut spec = eval("let @a = {}; res / ( get -> @a );")?;
+
+    // Recursive declarations cannot be expressed in the language yet.
+    let recursive = Schema {
+        expr: SchemaExpr::Object(Object {
+            props: vec![Property {
+                name: "next".into(),
+                schema: Schema {
+                    expr: SchemaExpr::Ref("@a".into()),
+                    desc: None,
+                    title: None,
+                    required: None,
+                },
+                desc: None,
+                required: None,
+            }],
+        }),
+        desc: None,
+        title: None,
+        required: None,
+    };
+    spec.refs.insert("@a".into(), Reference::Schema(recursive));
+
+    let api = Builder::new()
+        .with_spec(spec)
+        .with_dereference(true)
+        .into_openapi();
+
+    let schemas = &api
+        .components
+        .as_ref()
+        .expect("expected components")
+        .schemas;
+
+    assert_eq!(schemas.len(), 1);
+    assert!(schemas.contains_key("a"));
+
+    match response_schema(&api) {
+        ReferenceOr::Item(s) => match &s.schema_kind {
+            SchemaKind::Type(Type::Object(o)) => assert!(matches!(
+                &o.properties["next"],
+                ReferenceOr::Reference { reference } if reference == "#/components/schemas/a"
+            )),
+            _ => panic!("expected object schema"),
+        },
+        _ => panic!("expected inline schema"),
+    }
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn builder_examples() -> anyhow::Result<()> {
+    let code = r#"
+        let @a = { 'id int `minimum: 1`, 'tag str `enum: [x]` };
+        res /a/{ 'id str `pattern: "^[0-9]{3}$"` }?{ 'n int `example: 7` } (
+          put : <@a> -> @a
+        );
+    "#;
+    let spec = eval(code)?;
+
+    let api = Builder::new().with_spec(spec.clone()).into_openapi();
+    let op = api.paths.paths["/a/{id}"]
+        .as_item()
+        .and_then(|p| p.put.as_ref())
+        .expect("expected put operation");
+    let request = op.request_body.as_ref().and_then(ReferenceOr::as_item);
+    assert!(request.unwrap().content["application/json"]
+        .example
+        .is_none());
+
+    let api = Builder::new()
+        .with_spec(spec)
+        .with_examples(Some(3))
+        .into_openapi();
+    let item = api.paths.paths["/a/{id}"].as_item().unwrap();
+
+    let params: Vec<_> = item
+        .parameters
+        .iter()
+        .filter_map(ReferenceOr::as_item)
+        .map(|p| p.parameter_data_ref())
+        .collect();
+    let id = params[0].example.as_ref().expect("expected path example");
+    assert_eq!(id.as_str().map(str::len), Some(3));
+    assert!(
+        params[1].example.is_none(),
+        "a given example should be kept on the schema"
+    );
+
+    let op = item.put.as_ref().expect("expected put operation");
+    let request = op.request_body.as_ref().and_then(ReferenceOr::as_item);
+    let example = request.unwrap().content["application/json"]
+        .example
+        .as_ref()
+        .expect("expected request example");
+    assert_eq!(example["tag"], "x");
+    assert!(example["id"].as_i64().unwrap() >= 1);
+
+    let components = api.components.as_ref().unwrap();
+    let component = components.schemas["a"].as_item().unwrap();
+    assert_eq!(component.schema_data.example.as_ref(), Some(example));
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn builder_examples_given() -> anyhow::Result<()> {
+    let code = r#"
+        let @id = str `example: "abc"`;
+        let @ref = @id;
+        res / ( get -> @ref );
+    "#;
+    let spec = eval(code)?;
+
+    let api = Builder::new()
+        .with_spec(spec)
+        .with_examples(Some(3))
+        .into_openapi();
+
+    let op = api.paths.paths["/"]
+        .as_item()
+        .and_then(|p| p.get.as_ref())
+        .expect("expected get operation");
+    let response = op.responses.default.as_ref().and_then(ReferenceOr::as_item);
+    assert!(
+        response.unwrap().content["application/json"]
+            .example
+            .is_none(),
+        "the example given to the referenced schema should be used"
+    );
+
+    let components = api.components.as_ref().unwrap();
+    let component = components.schemas["id"].as_item().unwrap();
+    assert_eq!(component.schema_data.example, Some("abc".into()));
+
+    anyhow::Ok(())
+}
@@ -0,0 +1,832 @@
+use crate::errors::{Error, Kind, Result};
+use indexmap::IndexMap;
+use openapiv3::{
+    Header, MediaType, OpenAPI, Operation, Parameter, ParameterData, ParameterSchemaOrContent,
+    PathItem, ReferenceOr, RequestBody, Response, Schema, SchemaKind, StatusCode, StringType, Type,
+    VariantOrUnknownOrEmpty,
+};
+use serde_json::Value;
+use std::borrow::Borrow;
+
+const SCHEMAS: &str = "#/components/schemas/";
+const PARAMETERS: &str = "#/components/parameters/";
+const REQUEST_BODIES: &str = "#/components/requestBodies/";
+const RESPONSES: &str = "#/components/responses/";
+const HEADERS: &str = "#/components/headers/";
+
+/// Key-value pairs rendered as an inline annotation.
+type Annotation = Vec<(&'static str, Value)>;
+
+/// Renders an inline annotation, if not empty.
+fn annotation(ann: &Annotation) -> String {
+    if ann.is_empty() {
+        String::new()
+    } else {
+        let props: Vec<_> = ann
+            .iter()
+            .map(|(k, v)| format!("{}: {}", k, v.to_string().replace('`', "\\u0060")))
+            .collect();
+        format!(" `{}`", props.join(", "))
+    }
+}
+
+/// Converts a component name into a reference identifier.
+fn reference_ident(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '$' || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("@{}", name)
+}
+
+/// Assigns a distinct reference identifier to each schema component.
+///
+/// Components whose name is already a valid identifier keep it. Others are cleaned,
+/// then suffixed with a number if the cleaned name is taken.
+fn reference_idents(api: &OpenAPI) -> IndexMap<&str, String> {
+    let names: Vec<_> = api
+        .components
+        .iter()
+        .flat_map(|c| c.schemas.keys())
+        .map(String::as_str)
+        .collect();
+    let (valid, cleaned): (Vec<_>, Vec<_>) = names
+        .into_iter()
+        .partition(|n| reference_ident(n)[1..] == **n);
+    let mut idents: IndexMap<&str, String> =
+        valid.into_iter().map(|n| (n, reference_ident(n))).collect();
+    for name in cleaned {
+        let ident = reference_ident(name);
+        let mut candidate = ident.clone();
+        let mut n = 2;
+        while idents.values().any(|i| *i == candidate) {
+            candidate = format!("{}{}", ident, n);
+            n += 1;
+        }
+        idents.insert(name, candidate);
+    }
+    idents
+}
+
+fn is_keyname(name: &str) -> bool {
+    name.chars()
+        .all(|c| c.is_ascii_alphanumeric() || "@$-_".contains(c))
+}
+
+fn is_uri_literal(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-._~%".contains(c))
+}
+
+fn parameter_data(p: &Parameter) -> &ParameterData {
+    match p {
+        Parameter::Query { parameter_data, .. }
+        | Parameter::Header { parameter_data, .. }
+        | Parameter::Path { parameter_data, .. }
+        | Parameter::Cookie { parameter_data, .. } => parameter_data,
+    }
+}
+
+/// Renders a property, with an annotation on the property itself if not empty.
+///
+/// Names with other than keyname characters are quoted.
+fn property(name: &str, value: String, ann: &Annotation) -> Result<String> {
+    let prop = if !name.is_empty() && is_keyname(name) {
+        format!("'{} {}", name, value)
+    } else if !name.contains('"') {
+        format!("'\"{}\" {}", name, value)
+    } else {
+        return Err(Error::new(Kind::Unsupported, "invalid property name").with(&name));
+    };
+    if ann.is_empty() {
+        Ok(prop)
+    } else {
+        Ok(format!("({}){}", prop, annotation(ann)))
+    }
+}
+
+fn object(props: &[String]) -> String {
+    if props.is_empty() {
+        "{}".to_owned()
+    } else {
+        format!("{{ {} }}", props.join(", "))
+    }
+}
+
+/// A schema expression, with its precedence.
+struct Expr {
+    text: String,
+    /// Whether the expression is a variadic operation
+    is_op: bool,
+}
+
+impl Expr {
+    fn term(text: String) -> Self {
+        Expr { text, is_op: false }
+    }
+
+    /// Renders the expression where a term is expected.
+    fn into_term(self, ann: &Annotation) -> String {
+        let text = if self.is_op {
+            format!("({})", self.text)
+        } else {
+            self.text
+        };
+        format!("{}{}", text, annotation(ann))
+    }
+
+    /// Renders the expression where any expression is expected.
+    fn into_expr(self, ann: &Annotation) -> String {
+        if ann.is_empty() {
+            self.text
+        } else {
+            self.into_term(ann)
+        }
+    }
+}
+
+struct Decompiler<'a> {
+    api: &'a OpenAPI,
+    /// The reference identifier of each schema component
+    idents: IndexMap<&'a str, String>,
+}
+
+impl<'a> Decompiler<'a> {
+    /// Resolves a local reference to a component.
+    fn resolve<T>(
+        &self,
+        r: &'a ReferenceOr<T>,
+        prefix: &str,
+        get: impl Fn(&'a openapiv3::Components) -> &'a IndexMap<String, ReferenceOr<T>>,
+    ) -> Result<&'a T> {
+        match r {
+            ReferenceOr::Item(t) => Ok(t),
+            ReferenceOr::Reference { reference } => {
+                let target = reference
+                    .strip_prefix(prefix)
+                    .and_then(|name| self.api.components.as_ref().map(|c| get(c).get(name)))
+                    .flatten()
+                    .ok_or_else(|| {
+                        Error::new(Kind::Unsupported, "unresolved reference").with(reference)
+                    })?;
+                self.resolve(target, prefix, get)
+            }
+        }
+    }
+
+    fn ident(&self, name: &str) -> String {
+        self.idents
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| reference_ident(name))
+    }
+
+    fn schema_ref(&self, reference: &str) -> Result<String> {
+        reference
+            .strip_prefix(SCHEMAS)
+            .map(|name| self.ident(name))
+            .ok_or_else(|| Error::new(Kind::Unsupported, "unsupported reference").with(&reference))
+    }
+
+    fn schema_annotation(&self, s: &Schema) -> Annotation {
+        let mut ann = Annotation::new();
+        if let Some(title) = &s.schema_data.title {
+            ann.push(("title", title.as_str().into()));
+        }
+        if let Some(desc) = &s.schema_data.description {
+            ann.push(("description", desc.as_str().into()));
+        }
+        ann
+    }
+
+    fn number_annotation<N: Into<Value>>(
+        &self,
+        ann: &mut Annotation,
+        minimum: Option<N>,
+        maximum: Option<N>,
+        multiple_of: Option<N>,
+    ) {
+        if let Some(v) = minimum {
+            ann.push(("minimum", v.into()));
+        }
+        if let Some(v) = maximum {
+            ann.push(("maximum", v.into()));
+        }
+        if let Some(v) = multiple_of {
+            ann.push(("multipleOf", v.into()));
+        }
+    }
+
+    fn string_expr(&self, t: &StringType, ann: &mut Annotation) -> Expr {
+        if matches!(&t.format, VariantOrUnknownOrEmpty::Unknown(f) if f == "uri-reference") {
+            return Expr::term("uri".to_owned());
+        }
+        if let Some(p) = &t.pattern {
+            ann.push(("pattern", p.as_str().into()));
+        }
+        let enumeration: Vec<Value> = t
+            .enumeration
+            .iter()
+            .flatten()
+            .map(|s| s.as_str().into())
+            .collect();
+        if !enumeration.is_empty() {
+            ann.push(("enum", enumeration.into()));
+        }
+        Expr::term("str".to_owned())
+    }
+
+    fn object_expr(
+        &self,
+        properties: &IndexMap<String, ReferenceOr<Box<Schema>>>,
+        required: &[String],
+    ) -> Result<Expr> {
+        let props = properties
+            .iter()
+            .map(|(name, s)| {
+                let mut extra = Annotation::new();
+                if required.contains(name) {
+                    extra.push(("required", true.into()));
+                }
+                let value = self.schema_term(s, extra)?;
+                property(name, value, &Annotation::new())
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Expr::term(object(&props)))
+    }
+
+    fn op_expr(&self, schemas: &[ReferenceOr<Schema>], op: &str) -> Result<Expr> {
+        let operands = schemas
+            .iter()
+            .map(|s| self.schema_term(s, Annotation::new()))
+            .collect::<Result<Vec<_>>>()?;
+        match operands.len() {
+            0 => Ok(Expr::term("{}".to_owned())),
+            1 => Ok(Expr::term(operands.into_iter().next().unwrap())),
+            _ => Ok(Expr {
+                text: operands.join(op),
+                is_op: true,
+            }),
+        }
+    }
+
+    /// Decompiles a schema, accumulating its annotation.
+    fn schema_expr(&self, s: &Schema, ann: &mut Annotation) -> Result<Expr> {
+        let example = s.schema_data.example.clone();
+        let expr = match &s.schema_kind {
+            SchemaKind::Type(Type::String(t)) => self.string_expr(t, ann),
+            SchemaKind::Type(Type::Number(t)) => {
+                self.number_annotation(ann, t.minimum, t.maximum, t.multiple_of);
+                Expr::term("num".to_owned())
+            }
+            SchemaKind::Type(Type::Integer(t)) => {
+                self.number_annotation(ann, t.minimum, t.maximum, t.multiple_of);
+                Expr::term("int".to_owned())
+            }
+            SchemaKind::Type(Type::Boolean { .. }) => Expr::term("bool".to_owned()),
+            SchemaKind::Type(Type::Object(t)) => self.object_expr(&t.properties, &t.required)?,
+            SchemaKind::Type(Type::Array(t)) => {
+                let item = match &t.items {
+                    Some(item) => self.schema(item)?,
+                    None => "{}".to_owned(),
+                };
+                Expr::term(format!("[{}]", item))
+            }
+            SchemaKind::AllOf { all_of } => self.op_expr(all_of, " & ")?,
+            SchemaKind::OneOf { one_of } => self.op_expr(one_of, " | ")?,
+            SchemaKind::AnyOf { any_of } => self.op_expr(any_of, " ~ ")?,
+            SchemaKind::Any(any) => match &any.items {
+                Some(item) => Expr::term(format!("[{}]", self.schema(item)?)),
+                None => self.object_expr(&any.properties, &any.required)?,
+            },
+            SchemaKind::Not { .. } => {
+                return Err(Error::new(Kind::Unsupported, "negated schema").with(s))
+            }
+        };
+        if let Some(e) = example {
+            ann.push(("example", e));
+        }
+        Ok(expr)
+    }
+
+    /// Decompiles a schema where a term is expected, with some extra annotation.
+    fn schema_term<S: Borrow<Schema>>(
+        &self,
+        s: &ReferenceOr<S>,
+        extra: Annotation,
+    ) -> Result<String> {
+        match s {
+            ReferenceOr::Reference { reference } => Ok(format!(
+                "{}{}",
+                self.schema_ref(reference)?,
+                annotation(&extra)
+            )),
+            ReferenceOr::Item(s) => {
+                let s = s.borrow();
+                let mut ann = self.schema_annotation(s);
+                let expr = self.schema_expr(s, &mut ann)?;
+                ann.extend(extra);
+                Ok(expr.into_term(&ann))
+            }
+        }
+    }
+
+    /// Decompiles a schema where any expression is expected.
+    fn schema<S: Borrow<Schema>>(&self, s: &ReferenceOr<S>) -> Result<String> {
+        match s {
+            ReferenceOr::Reference { reference } => self.schema_ref(reference),
+            ReferenceOr::Item(s) => {
+                let s = s.borrow();
+                let mut ann = self.schema_annotation(s);
+                let expr = self.schema_expr(s, &mut ann)?;
+                Ok(expr.into_expr(&ann))
+            }
+        }
+    }
+
+    fn deps<S: Borrow<Schema>>(&self, r: &ReferenceOr<S>, deps: &mut Vec<String>) {
+        match r {
+            ReferenceOr::Reference { reference } => deps.push(reference.clone()),
+            ReferenceOr::Item(s) => self.schema_deps(s.borrow(), deps),
+        }
+    }
+
+    /// Collects the schema components referenced by a schema.
+    fn schema_deps(&self, s: &Schema, deps: &mut Vec<String>) {
+        match &s.schema_kind {
+            SchemaKind::Type(Type::Object(t)) => {
+                t.properties.values().for_each(|r| self.deps(r, deps))
+            }
+            SchemaKind::Type(Type::Array(t)) => t.items.iter().for_each(|r| self.deps(r, deps)),
+            SchemaKind::Any(any) => {
+                any.properties.values().for_each(|r| self.deps(r, deps));
+                any.items.iter().for_each(|r| self.deps(r, deps));
+            }
+            SchemaKind::AllOf { all_of: schemas }
+            | SchemaKind::OneOf { one_of: schemas }
+            | SchemaKind::AnyOf { any_of: schemas } => {
+                schemas.iter().for_each(|r| self.deps(r, deps))
+            }
+            _ => {}
+        }
+    }
+
+    /// Sorts the schema components so that each one is declared after its dependencies.
+    fn sorted_schemas(&self) -> Result<Vec<(&'a String, &'a ReferenceOr<Schema>)>> {
+        let schemas = match &self.api.components {
+            Some(c) => &c.schemas,
+            None => return Ok(Vec::new()),
+        };
+        let mut sorted = Vec::new();
+        let mut visiting = Vec::new();
+        fn visit<'a>(
+            this: &Decompiler<'a>,
+            schemas: &'a IndexMap<String, ReferenceOr<Schema>>,
+            name: &'a String,
+            sorted: &mut Vec<(&'a String, &'a ReferenceOr<Schema>)>,
+            visiting: &mut Vec<&'a String>,
+        ) -> Result<()> {
+            if sorted.iter().any(|(n, _)| *n == name) {
+                return Ok(());
+            }
+            if visiting.contains(&name) {
+                return Err(Error::new(Kind::Unsupported, "recursive schema").with(name));
+            }
+            let schema = match schemas.get(name) {
+                Some(s) => s,
+                None => {
+                    return Err(Error::new(Kind::Unsupported, "unresolved reference").with(name))
+                }
+            };
+            visiting.push(name);
+            let mut deps = Vec::new();
+            this.deps(schema, &mut deps);
+            for dep in deps {
+                if let Some((dep, _)) = dep
+                    .strip_prefix(SCHEMAS)
+                    .and_then(|d| schemas.get_key_value(d))
+                {
+                    visit(this, schemas, dep, sorted, visiting)?;
+                }
+            }
+            visiting.pop();
+            sorted.push((name, schema));
+            Ok(())
+        }
+        for name in schemas.keys() {
+            visit(self, schemas, name, &mut sorted, &mut visiting)?;
+        }
+        Ok(sorted)
+    }
+
+    fn param_schema(&self, format: &ParameterSchemaOrContent) -> Result<String> {
+        match format {
+            ParameterSchemaOrContent::Schema(s) => self.schema_term(s, Annotation::new()),
+            ParameterSchemaOrContent::Content(_) => {
+                Err(Error::new(Kind::Unsupported, "parameter content").with(format))
+            }
+        }
+    }
+
+    fn param_property(&self, data: &ParameterData, required: bool) -> Result<String> {
+        let mut ann = Annotation::new();
+        if let Some(desc) = &data.description {
+            ann.push(("description", desc.as_str().into()));
+        }
+        if required && data.required {
+            ann.push(("required", true.into()));
+        }
+        property(&data.name, self.param_schema(&data.format)?, &ann)
+    }
+
+    fn header_property(&self, name: &str, h: &Header) -> Result<String> {
+        let mut ann = Annotation::new();
+        if let Some(desc) = &h.description {
+            ann.push(("description", desc.as_str().into()));
+        }
+        if h.required {
+            ann.push(("required", true.into()));
+        }
+        property(name, self.param_schema(&h.format)?, &ann)
+    }
+
+    fn headers(&self, headers: &'a IndexMap<String, ReferenceOr<Header>>) -> Result<Vec<String>> {
+        headers
+            .iter()
+            .map(|(name, h)| {
+                let h = self.resolve(h, HEADERS, |c| &c.headers)?;
+                self.header_property(name, h)
+            })
+            .collect()
+    }
+
+    fn content(
+        &self,
+        status: Option<String>,
+        media: Option<&str>,
+        headers: &[String],
+        schema: Option<String>,
+        desc: Option<&str>,
+    ) -> String {
+        let mut props = Vec::new();
+        if let Some(s) = status {
+            props.push(format!("status={}, ", s));
+        }
+        if let Some(m) = media.filter(|m| *m != "application/json") {
+            props.push(format!("media=\"{}\", ", m));
+        }
+        if !headers.is_empty() {
+            props.push(format!("headers={}, ", object(headers)));
+        }
+        let mut ann = Annotation::new();
+        if let Some(d) = desc.filter(|d| !d.is_empty()) {
+            ann.push(("description", d.into()));
+        }
+        match schema {
+            Some(s) => format!("<{}{}>{}", props.concat(), s, annotation(&ann)),
+            None => format!("<{}>{}", props.concat().trim_end(), annotation(&ann)),
+        }
+    }
+
+    fn media_schema(&self, media: &MediaType) -> Result<Option<String>> {
+        media.schema.as_ref().map(|s| self.schema(s)).transpose()
+    }
+
+    fn domain(&self, body: Option<&'a RequestBody>, headers: &[String]) -> Result<Option<String>> {
+        let body = match body {
+            Some(b) if !b.content.is_empty() => b,
+            _ if headers.is_empty() => return Ok(None),
+            _ => return Ok(Some(self.content(None, None, headers, None, None))),
+        };
+        if body.content.len() > 1 {
+            return Err(
+                Error::new(Kind::Unsupported, "multiple request media types")
+                    .with(&body.content.keys().collect::<Vec<_>>()),
+            );
+        }
+        let (media, content) = body.content.first().unwrap();
+        let schema = self.media_schema(content)?;
+        Ok(Some(self.content(
+            None,
+            Some(media),
+            headers,
+            schema,
+            body.description.as_deref(),
+        )))
+    }
+
+    fn status(&self, code: &StatusCode) -> String {
+        match code {
+            StatusCode::Code(c) => c.to_string(),
+            StatusCode::Range(r) => format!("{}XX", r),
+        }
+    }
+
+    fn ranges(&self, op: &'a Operation) -> Result<Vec<String>> {
+        let responses = op
+            .responses
+            .responses
+            .iter()
+            .map(|(code, r)| (Some(self.status(code)), r))
+            .chain(op.responses.default.iter().map(|r| (None, r)));
+        let mut ranges = Vec::new();
+        for (status, r) in responses {
+            let res: &Response = self.resolve(r, RESPONSES, |c| &c.responses)?;
+            let headers = self.headers(&res.headers)?;
+            let desc = Some(res.description.as_str());
+            if res.content.is_empty() {
+                ranges.push(self.content(status.clone(), None, &headers, None, desc));
+            }
+            for (media, content) in res.content.iter() {
+                let schema = self.media_schema(content)?;
+                ranges.push(self.content(status.clone(), Some(media), &headers, schema, desc));
+            }
+        }
+        if ranges.is_empty() {
+            ranges.push("<>".to_owned());
+        }
+        Ok(ranges)
+    }
+
+    fn parameters(&self, params: &'a [ReferenceOr<Parameter>]) -> Result<Vec<&'a Parameter>> {
+        params
+            .iter()
+            .map(|p| self.resolve(p, PARAMETERS, |c| &c.parameters))
+            .collect()
+    }
+
+    fn transfer(
+        &self,
+        method: &str,
+        op: &'a Operation,
+        common: &[&'a Parameter],
+    ) -> Result<String> {
+        let params = self.parameters(&op.parameters)?;
+        let mut query = Vec::new();
+        let mut headers = Vec::new();
+        // Common query parameters belong to the URI, others apply unless overridden.
+        let overridden = |p: &Parameter| {
+            params.iter().any(|q| {
+                std::mem::discriminant(*q) == std::mem::discriminant(p)
+                    && parameter_data(q).name == parameter_data(p).name
+            })
+        };
+        let all = common
+            .iter()
+            .filter(|p| !matches!(p, Parameter::Query { .. }) && !overridden(p))
+            .chain(params.iter());
+        for p in all {
+            match p {
+                Parameter::Query { parameter_data, .. } => {
+                    query.push(self.param_property(parameter_data, true)?)
+                }
+                Parameter::Header { parameter_data, .. } => {
+                    headers.push(self.param_property(parameter_data, true)?)
+                }
+                Parameter::Path { .. } => {}
+                Parameter::Cookie { parameter_data, .. } => {
+                    return Err(Error::new(Kind::Unsupported, "cookie parameter")
+                        .with(&parameter_data.name))
+                }
+            }
+        }
+
+        let body = op
+            .request_body
+            .as_ref()
+            .map(|b| self.resolve(b, REQUEST_BODIES, |c| &c.request_bodies))
+            .transpose()?;
+
+        let mut xfer = method.to_owned();
+        if !query.is_empty() {
+            xfer.push(' ');
+            xfer.push_str(&object(&query));
+        }
+        if let Some(domain) = self.domain(body, &headers)? {
+            xfer.push_str(" : ");
+            xfer.push_str(&domain);
+        }
+        xfer.push_str(" -> ");
+        xfer.push_str(&self.ranges(op)?.join("\n      :: "));
+
+        let mut ann = Annotation::new();
+        if let Some(s) = &op.summary {
+            ann.push(("summary", s.as_str().into()));
+        }
+        if let Some(d) = &op.description {
+            ann.push(("description", d.as_str().into()));
+        }
+        if let Some(id) = &op.operation_id {
+            ann.push(("operationId", id.as_str().into()));
+        }
+        if !op.tags.is_empty() {
+            ann.push((
+                "tags",
+                op.tags.iter().map(|t| Value::from(t.as_str())).collect(),
+            ));
+        }
+        if ann.is_empty() {
+            Ok(xfer)
+        } else {
+            Ok(format!("({}){}", xfer, annotation(&ann)))
+        }
+    }
+
+    fn operations(item: &'a PathItem) -> Result<Vec<(&'static str, &'a Operation)>> {
+        if item.trace.is_some() {
+            return Err(Error::new(Kind::Unsupported, "trace operation"));
+        }
+        let ops = [
+            ("get", &item.get),
+            ("put", &item.put),
+            ("post", &item.post),
+            ("patch", &item.patch),
+            ("delete", &item.delete),
+            ("options", &item.options),
+            ("head", &item.head),
+        ];
+        Ok(ops
+            .into_iter()
+            .filter_map(|(m, op)| op.as_ref().map(|op| (m, op)))
+            .collect())
+    }
+
+    /// Decompiles the template of a path into a URI.
+    fn uri(
+        &self,
+        path: &str,
+        item: &'a PathItem,
+        common: &[&'a Parameter],
+        ops: &[(&str, &'a Operation)],
+    ) -> Result<String> {
+        let mut all = common.to_vec();
+        for (_, op) in ops {
+            all.extend(self.parameters(&op.parameters)?);
+        }
+        let mut uri = String::new();
+        for segment in path.split('/').filter(|s| !s.is_empty()) {
+            uri.push('/');
+            if let Some(var) = segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                let data = all
+                    .iter()
+                    .find_map(|p| match p {
+                        Parameter::Path { parameter_data, .. } if parameter_data.name == var => {
+                            Some(parameter_data)
+                        }
+                        _ => None,
+                    })
+                    .ok_or_else(|| {
+                        Error::new(Kind::Unsupported, "undeclared path parameter").with(&var)
+                    })?;
+                uri.push_str(&format!("{{ {} }}", self.param_property(data, false)?));
+            } else if is_uri_literal(segment) {
+                uri.push_str(segment);
+            } else {
+                return Err(Error::new(Kind::Unsupported, "invalid path segment").with(&segment));
+            }
+        }
+        if uri.is_empty() {
+            uri.push('/');
+        }
+        let query = common
+            .iter()
+            .filter_map(|p| match p {
+                Parameter::Query { parameter_data, .. } => Some(parameter_data),
+                _ => None,
+            })
+            .map(|data| self.param_property(data, true))
+            .collect::<Result<Vec<_>>>()?;
+        if !query.is_empty() {
+            if uri == "/" {
+                return Err(
+                    Error::new(Kind::Unsupported, "query parameters on root path")
+                        .with(&item.parameters),
+                );
+            }
+            uri.push('?');
+            uri.push_str(&object(&query));
+        }
+        Ok(uri)
+    }
+
+    fn relation(&self, path: &str, item: &'a ReferenceOr<PathItem>) -> Result<String> {
+        let item = match item {
+            ReferenceOr::Item(i) => i,
+            ReferenceOr::Reference { reference } => {
+                return Err(Error::new(Kind::Unsupported, "path item reference").with(reference))
+            }
+        };
+        let common = self.parameters(&item.parameters)?;
+        let ops = Self::operations(item)?;
+        let uri = self.uri(path, item, &common, &ops)?;
+        let xfers = ops
+            .iter()
+            .map(|(m, op)| self.transfer(m, op, &common))
+            .collect::<Result<Vec<_>>>()?;
+        if xfers.is_empty() {
+            Ok(format!("res {};", uri))
+        } else {
+            Ok(format!("res {} (\n  {}\n);", uri, xfers.join(",\n  ")))
+        }
+    }
+
+    fn program(&self) -> Result<String> {
+        let mut stmts = Vec::new();
+        for (name, schema) in self.sorted_schemas()? {
+            stmts.push(format!(
+                "let {} = {};",
+                self.ident(name),
+                self.schema(schema)?
+            ));
+        }
+        for (path, item) in self.api.paths.paths.iter() {
+            stmts.push(self.relation(path, item)?);
+        }
+        Ok(stmts.join("\n\n") + "\n")
+    }
+}
+
+/// Decompiles an OpenAPI definition into the source of a program.
+///
+/// Schema components become reference declarations and paths become resources.
+/// Only local references to components are supported. Component names which are not valid
+/// identifiers are cleaned, and numbered if they collide with another.
+/// Constructs without an equivalent in the language, like recursive schemas or cookie
+/// parameters, are reported as errors.
+pub fn decompile(api: &OpenAPI) -> Result<String> {
+    let idents = reference_idents(api);
+    Decompiler { api, idents }.program()
+}
+
+/// Rewrites JSON Schema definition references into schema component references.
+fn rewrite_refs(v: &mut Value) {
+    match v {
+        Value::Object(m) => {
+            for (k, v) in m.iter_mut() {
+                match v {
+                    Value::String(r) if k == "$ref" => {
+                        for prefix in ["#/$defs/", "#/definitions/"] {
+                            if let Some(name) = r.strip_prefix(prefix) {
+                                *r = format!("{}{}", SCHEMAS, name);
+                            }
+                        }
+                    }
+                    _ => rewrite_refs(v),
+                }
+            }
+        }
+        Value::Array(a) => a.iter_mut().for_each(rewrite_refs),
+        _ => {}
+    }
+}
+
+/// Converts a JSON Schema document into an OpenAPI definition with schema components.
+///
+/// Schemas come from `$defs` or `definitions`, as well as the root schema if it has a title.
+fn json_schema_components(mut doc: Value) -> Result<OpenAPI> {
+    rewrite_refs(&mut doc);
+    let mut schemas = IndexMap::new();
+    if let Value::Object(root) = &mut doc {
+        for key in ["$defs", "definitions"] {
+            if let Some(Value::Object(defs)) = root.remove(key) {
+                for (name, def) in defs {
+                    schemas.insert(name, serde_json::from_value(def)?);
+                }
+            }
+        }
+        if let Some(Value::String(title)) = root.get("title").cloned() {
+            root.remove("$schema");
+            root.remove("$id");
+            schemas.insert(title, serde_json::from_value(doc)?);
+        }
+    }
+    Ok(OpenAPI {
+        components: Some(openapiv3::Components {
+            schemas,
+            ..Default::default()
+        }),
+        ..Default::default()
+    })
+}
+
+/// Decompiles the schemas of an OpenAPI or JSON Schema document into the source of a module.
+///
+/// Only schemas are decompiled, as reference declarations, so that the document can be
+/// imported by other programs.
+pub fn decompile_module(doc: Value) -> Result<String> {
+    let api = if doc.get("openapi").is_some() {
+        let mut api: OpenAPI = serde_json::from_value(doc)?;
+        api.paths = Default::default();
+        api
+    } else {
+        json_schema_components(doc)?
+    };
+    decompile(&api)
+}
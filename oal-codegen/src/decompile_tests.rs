@@ -0,0 +1,198 @@
+use crate::errors::Kind;
+use crate::tests::eval;
+use crate::{decompile, decompile_module, Builder};
+use oal_compiler::spec::{Reference, Schema, SchemaExpr, Spec};
+use oal_compiler::{Locator, ModuleSet, Program};
+use oal_syntax::atom::Ident;
+use oal_syntax::parse;
+use openapiv3::OpenAPI;
+
+#[test]
+fn decompile_roundtrip() -> anyhow::Result<()> {
+    let api: OpenAPI = serde_yaml::from_str(include_str!("../../examples/openapi.yaml"))?;
+
+    let code = decompile(&api)?;
+    let spec = eval(&code)?;
+    let output = Builder::new()
+        .with_spec(spec)
+        .with_base(api.clone())
+        .into_openapi();
+
+    assert_eq!(output, api);
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn decompile_recursive() -> anyhow::Result<()> {
+    let api: OpenAPI = serde_yaml::from_str(
+        r##"
+openapi: 3.0.3
+info:
+  title: Test
+  version: 0.1.0
+paths: {}
+components:
+  schemas:
+    Node:
+      type: object
+      properties:
+        next:
+          $ref: "#/components/schemas/Node"
+"##,
+    )?;
+
+    let err = decompile(&api).expect_err("expected error");
+
+    assert_eq!(err.kind, Kind::Unsupported);
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn decompile_property_names() -> anyhow::Result<()> {
+    let api: OpenAPI = serde_yaml::from_str(
+        r##"
+openapi: 3.0.3
+info:
+  title: Test
+  version: 0.1.0
+paths: {}
+components:
+  schemas:
+    Person:
+      type: object
+      properties:
+        first.name:
+          type: string
+        a b:
+          type: integer
+"##,
+    )?;
+
+    let code = decompile(&api)?;
+
+    assert!(code.contains(r#"'"first.name" str"#));
+    assert!(code.contains(r#"'"a b" int"#));
+
+    let spec = eval(&format!("{}res / ( get -> @Person );", code))?;
+    let Reference::Schema(person) = &spec.refs[&Ident::from("@Person")];
+    let SchemaExpr::Object(person) = &person.expr else {
+        panic!("expected object")
+    };
+    let names: Vec<_> = person.props.iter().map(|p| p.name.as_ref()).collect();
+
+    assert_eq!(names, vec!["first.name", "a b"]);
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn decompile_colliding_names() -> anyhow::Result<()> {
+    let api: OpenAPI = serde_yaml::from_str(
+        r##"
+openapi: 3.0.3
+info:
+  title: Test
+  version: 0.1.0
+paths: {}
+components:
+  schemas:
+    Order.Item:
+      type: string
+    Order_Item:
+      type: integer
+    Order:
+      type: object
+      properties:
+        a:
+          $ref: "#/components/schemas/Order.Item"
+        b:
+          $ref: "#/components/schemas/Order_Item"
+"##,
+    )?;
+
+    let code = decompile(&api)?;
+
+    assert!(code.contains("let @Order_Item = int;"));
+    assert!(code.contains("let @Order_Item2 = str;"));
+    assert!(code.contains("'a @Order_Item2"));
+    assert!(code.contains("'b @Order_Item"));
+
+    let spec = eval(&format!("{}res / ( get -> @Order );", code))?;
+    let Reference::Schema(order) = &spec.refs[&Ident::from("@Order")];
+    let SchemaExpr::Object(order) = &order.expr else {
+        panic!("expected object")
+    };
+    let types: Vec<_> = order.props.iter().map(|p| &p.schema.expr).collect();
+
+    assert_eq!(
+        types,
+        vec![
+            &SchemaExpr::Ref(Ident::from("@Order_Item2")),
+            &SchemaExpr::Ref(Ident::from("@Order_Item")),
+        ]
+    );
+    assert!(matches!(
+        spec.refs[&Ident::from("@Order_Item2")],
+        Reference::Schema(Schema {
+            expr: SchemaExpr::Str(_),
+            ..
+        })
+    ));
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn decompile_json_schema_module() -> anyhow::Result<()> {
+    let doc = serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$defs": {
+            "Error": {
+                "type": "object",
+                "properties": {
+                    "code": { "type": "integer", "minimum": 100 },
+                    "detail": { "$ref": "#/$defs/Detail" },
+                },
+                "required": ["code"],
+            },
+            "Detail": { "type": "string" },
+        },
+    });
+
+    let module = &Locator::try_from("test:partner.json")?;
+    let main = &Locator::try_from("test:main.oal")?;
+    let source = decompile_module(doc)?;
+    let loader = |m: &Locator| -> anyhow::Result<Program> {
+        if m == module {
+            Ok(parse(source.as_str())?)
+        } else {
+            Ok(parse(
+                r#"use "test:partner.json"; res / ( get -> <status=500, @Error> );"#,
+            )?)
+        }
+    };
+    let compiler = |mods: &ModuleSet, l: &Locator, p: Program| -> anyhow::Result<Program> {
+        Ok(oal_compiler::compile(mods, l, p)?)
+    };
+    let mods = oal_compiler::load(main, loader, compiler)?;
+    let spec = Spec::try_from(&mods)?;
+
+    assert_eq!(
+        spec.origins.get(&Ident::from("@Error")).map(|s| &s.loc),
+        Some(module)
+    );
+
+    let api = Builder::new().with_spec(spec).into_openapi();
+    let schemas = &api
+        .components
+        .as_ref()
+        .expect("expected components")
+        .schemas;
+
+    assert!(schemas.contains_key("Error"));
+    assert!(schemas.contains_key("Detail"));
+
+    anyhow::Ok(())
+}
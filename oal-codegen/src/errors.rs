@@ -0,0 +1,57 @@
+use std::fmt::{Debug, Display, Formatter};
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Kind {
+    #[default]
+    Unknown,
+    InvalidDocument,
+    InvalidOverlay,
+    NoMatch,
+    Conflict,
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Error {
+    pub kind: Kind,
+    msg: String,
+    details: Vec<String>,
+}
+
+impl Error {
+    pub fn new<S: Into<String>>(kind: Kind, msg: S) -> Error {
+        Error {
+            kind,
+            msg: msg.into(),
+            details: Vec::new(),
+        }
+    }
+
+    pub fn with<T: Debug>(mut self, e: &T) -> Self {
+        self.details.push(format!("{:?}", e));
+        self
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(f, "{:?}: {}", self.kind, self.msg)?;
+        if !self.details.is_empty() {
+            writeln!(f, "Details:")?;
+            self.details
+                .iter()
+                .try_for_each(|d| writeln!(f, " {}", d))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::new(Kind::InvalidDocument, e.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,302 @@
+use oal_compiler::spec;
+use oal_syntax::{ast, atom};
+use regex_syntax::hir::{Class, Hir, HirKind};
+use serde_json::{Map, Value};
+
+/// The span of the values drawn when a number is only bounded on one side, or not at all.
+const SPAN: f64 = 100.0;
+
+/// The maximum number of extra repetitions drawn for unbounded pattern repetitions.
+const EXTRA_REPETITIONS: u32 = 3;
+
+/// The maximum number of items drawn for arrays.
+const MAX_ITEMS: u64 = 3;
+
+/// A small pseudo-random generator (SplitMix64), so that examples only depend on the seed.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Draws an integer in `0..n`, or zero if `n` is zero.
+    fn below(&mut self, n: u64) -> u64 {
+        if n == 0 {
+            0
+        } else {
+            self.next() % n
+        }
+    }
+
+    /// Draws a number in `0.0..1.0`.
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// A generator of example values for the schemas of a specification.
+///
+/// Examples given by the user are used as is. Otherwise, the value is the smallest one
+/// satisfying the bounds of numbers, the first value of enumerations, the shortest string
+/// matching patterns, and objects get all of their properties, except those referring to
+/// a schema being expanded. With a seed, values are drawn within the same constraints instead.
+pub struct Synthesizer<'a> {
+    refs: &'a spec::References,
+    /// The references being expanded, from the outermost to the innermost
+    expanding: Vec<atom::Ident>,
+    rng: Option<Rng>,
+}
+
+impl<'a> Synthesizer<'a> {
+    pub fn new(refs: &'a spec::References) -> Self {
+        Synthesizer {
+            refs,
+            expanding: Vec::new(),
+            rng: None,
+        }
+    }
+
+    /// Draws values pseudo-randomly, the same seed always giving the same values.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Some(Rng(seed));
+        self
+    }
+
+    /// Chooses an index in `0..n`, the first one without a seed.
+    fn pick(&mut self, n: usize) -> usize {
+        self.rng
+            .as_mut()
+            .map_or(0, |rng| rng.below(n as u64) as usize)
+    }
+
+    fn number(&mut self, p: &spec::PrimNumber) -> Value {
+        if let Some(e) = p.example {
+            return e.into();
+        }
+        let rng = match &mut self.rng {
+            Some(rng) => rng,
+            None => {
+                let mut n = p
+                    .minimum
+                    .unwrap_or_else(|| p.maximum.map_or(0.0, |max| max.min(0.0)));
+                if let Some(m) = p.multiple_of.filter(|m| *m > 0.0) {
+                    n = (n / m).ceil() * m;
+                }
+                return n.into();
+            }
+        };
+        let (lo, hi) = match (p.minimum, p.maximum) {
+            (Some(lo), Some(hi)) => (lo, hi.max(lo)),
+            (Some(lo), None) => (lo, lo + SPAN),
+            (None, Some(hi)) => (hi.min(0.0) - SPAN, hi),
+            (None, None) => (0.0, SPAN),
+        };
+        match p.multiple_of.filter(|m| *m > 0.0) {
+            Some(m) => {
+                let (first, last) = ((lo / m).ceil(), (hi / m).floor());
+                let k = first + rng.below((last - first).max(0.0) as u64 + 1) as f64;
+                (k * m).into()
+            }
+            None => (((lo + rng.unit() * (hi - lo)) * 100.0).round() / 100.0)
+                .clamp(lo, hi)
+                .into(),
+        }
+    }
+
+    fn integer(&mut self, p: &spec::PrimInteger) -> Value {
+        if let Some(e) = p.example {
+            return e.into();
+        }
+        // Bounds and multiples are computed on 128 bits so that the widest bounds cannot overflow.
+        let m = i128::from(p.multiple_of.filter(|m| *m > 0).unwrap_or(1));
+        let saturate =
+            |n: i128| i64::try_from(n).unwrap_or(if n < 0 { i64::MIN } else { i64::MAX });
+        let rng = match &mut self.rng {
+            Some(rng) => rng,
+            None => {
+                let n = i128::from(
+                    p.minimum
+                        .unwrap_or_else(|| p.maximum.map_or(0, |max| max.min(0))),
+                );
+                return saturate(n + (m - n.rem_euclid(m)) % m).into();
+            }
+        };
+        let span = SPAN as i64;
+        let (lo, hi) = match (p.minimum, p.maximum) {
+            (Some(lo), Some(hi)) => (lo, hi.max(lo)),
+            (Some(lo), None) => (lo, lo.saturating_add(span)),
+            (None, Some(hi)) => (hi.min(0).saturating_sub(span), hi),
+            (None, None) => (0, span),
+        };
+        let (lo, hi) = (i128::from(lo), i128::from(hi));
+        let first = lo.div_euclid(m) + i128::from(lo.rem_euclid(m) != 0);
+        let last = hi.div_euclid(m);
+        let count = u64::try_from((last - first).max(0) + 1).unwrap_or(u64::MAX);
+        let k = first + i128::from(rng.below(count));
+        saturate(k * m).into()
+    }
+
+    fn string(&mut self, p: &spec::PrimString) -> Value {
+        if let Some(e) = &p.example {
+            return e.clone().into();
+        }
+        if !p.enumeration.is_empty() {
+            let i = self.pick(p.enumeration.len());
+            return p.enumeration[i].clone().into();
+        }
+        p.pattern
+            .as_deref()
+            .and_then(|pattern| self.pattern(pattern))
+            .unwrap_or_else(|| "string".to_owned())
+            .into()
+    }
+
+    /// Returns a string matching a regular expression, if valid.
+    fn pattern(&mut self, pattern: &str) -> Option<String> {
+        let hir = regex_syntax::Parser::new().parse(pattern).ok()?;
+        let mut out = String::new();
+        self.hir(&hir, &mut out);
+        Some(out)
+    }
+
+    fn hir(&mut self, hir: &Hir, out: &mut String) {
+        match hir.kind() {
+            HirKind::Empty | HirKind::Look(_) => {}
+            HirKind::Literal(lit) => out.push_str(&String::from_utf8_lossy(&lit.0)),
+            HirKind::Class(Class::Unicode(class)) => {
+                // Printable ASCII characters are preferred when the class has some.
+                let ranges: Vec<_> = class
+                    .ranges()
+                    .iter()
+                    .map(|r| (u32::from(r.start()), u32::from(r.end())))
+                    .collect();
+                let printable: Vec<_> = ranges
+                    .iter()
+                    .filter(|(start, end)| *start <= 0x7E && *end >= 0x20)
+                    .map(|(start, end)| (*start.max(&0x20), *end.min(&0x7E)))
+                    .collect();
+                let ranges = if printable.is_empty() {
+                    ranges
+                } else {
+                    printable
+                };
+                if !ranges.is_empty() {
+                    let (start, end) = ranges[self.pick(ranges.len())];
+                    let offset = self.pick((end - start + 1) as usize) as u32;
+                    out.extend(char::from_u32(start + offset));
+                }
+            }
+            HirKind::Class(Class::Bytes(class)) => {
+                let ranges = class.ranges();
+                if !ranges.is_empty() {
+                    let range = ranges[self.pick(ranges.len())];
+                    let len = usize::from(range.end() - range.start()) + 1;
+                    let offset = self.pick(len) as u8;
+                    out.push(char::from(range.start() + offset));
+                }
+            }
+            HirKind::Repetition(rep) => {
+                let max = rep.max.unwrap_or(u32::MAX);
+                let extra = (max - rep.min).min(EXTRA_REPETITIONS);
+                let count = rep.min + self.pick(extra as usize + 1) as u32;
+                for _ in 0..count {
+                    self.hir(&rep.sub, out);
+                }
+            }
+            HirKind::Capture(cap) => self.hir(&cap.sub, out),
+            HirKind::Concat(subs) => subs.iter().for_each(|sub| self.hir(sub, out)),
+            HirKind::Alternation(subs) => {
+                let i = self.pick(subs.len());
+                self.hir(&subs[i], out)
+            }
+        }
+    }
+
+    fn boolean(&mut self) -> Value {
+        let b = match &mut self.rng {
+            Some(rng) => rng.below(2) == 0,
+            None => true,
+        };
+        Value::Bool(b)
+    }
+
+    fn uri(&self, uri: &spec::Uri) -> Value {
+        uri.example.clone().unwrap_or_else(|| uri.pattern()).into()
+    }
+
+    fn array(&mut self, array: &spec::Array) -> Value {
+        let count = match &mut self.rng {
+            Some(rng) => 1 + rng.below(MAX_ITEMS),
+            None => 1,
+        };
+        Value::Array((0..count).map(|_| self.schema(&array.item)).collect())
+    }
+
+    fn object(&mut self, obj: &spec::Object) -> Value {
+        let mut props = Map::new();
+        for p in obj.props.iter() {
+            if !self.is_expanding(&p.schema) {
+                props.insert(p.name.as_ref().to_owned(), self.schema(&p.schema));
+            }
+        }
+        Value::Object(props)
+    }
+
+    fn join(&mut self, schemas: &[spec::Schema]) -> Value {
+        schemas
+            .iter()
+            .map(|s| self.schema(s))
+            .fold(Value::Null, |acc, v| match (acc, v) {
+                (Value::Object(mut a), Value::Object(b)) => {
+                    a.extend(b);
+                    Value::Object(a)
+                }
+                (_, v) => v,
+            })
+    }
+
+    fn alternative(&mut self, schemas: &[spec::Schema]) -> Value {
+        if schemas.is_empty() {
+            Value::Null
+        } else {
+            let i = self.pick(schemas.len());
+            self.schema(&schemas[i])
+        }
+    }
+
+    fn is_expanding(&self, s: &spec::Schema) -> bool {
+        matches!(&s.expr, spec::SchemaExpr::Ref(name) if self.expanding.contains(name))
+    }
+
+    /// Returns an example value for a schema.
+    pub fn schema(&mut self, s: &spec::Schema) -> Value {
+        match &s.expr {
+            spec::SchemaExpr::Num(p) => self.number(p),
+            spec::SchemaExpr::Str(p) => self.string(p),
+            spec::SchemaExpr::Bool(_) => self.boolean(),
+            spec::SchemaExpr::Int(p) => self.integer(p),
+            spec::SchemaExpr::Rel(rel) => self.uri(&rel.uri),
+            spec::SchemaExpr::Uri(uri) => self.uri(uri),
+            spec::SchemaExpr::Array(array) => self.array(array),
+            spec::SchemaExpr::Object(obj) => self.object(obj),
+            spec::SchemaExpr::Op(op) => match op.op {
+                ast::Operator::Join => self.join(&op.schemas),
+                _ => self.alternative(&op.schemas),
+            },
+            spec::SchemaExpr::Ref(name) => match self.refs.get(name) {
+                Some(spec::Reference::Schema(referenced)) if !self.expanding.contains(name) => {
+                    self.expanding.push(name.clone());
+                    let value = self.schema(referenced);
+                    self.expanding.pop();
+                    value
+                }
+                _ => Value::Null,
+            },
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::tests::eval;
+use crate::Synthesizer;
+use oal_compiler::spec::Reference;
+use oal_syntax::atom::Ident;
+use serde_json::{json, Value};
+
+fn example(code: &str, name: &str) -> anyhow::Result<Value> {
+    let spec = eval(code)?;
+    let Reference::Schema(schema) = &spec.refs[&Ident::from(name)];
+    anyhow::Ok(Synthesizer::new(&spec.refs).schema(schema))
+}
+
+#[test]
+fn example_primitives() -> anyhow::Result<()> {
+    let code = r#"
+        let @a = {
+          'n num `minimum: 1.5, multipleOf: 2`
+        , 'i int `maximum: -3`
+        , 'j int `minimum: 5, multipleOf: 4`
+        , 's str `enum: [x, y]`
+        , 't str `example: "hello"`
+        , 'b bool
+        , 'u /a/{ 'id str }
+        , 'l [int]
+        };
+        res / ( get -> @a );
+    "#;
+
+    let value = example(code, "@a")?;
+
+    assert_eq!(
+        value,
+        json!({
+            "n": 2.0,
+            "i": -3,
+            "j": 8,
+            "s": "x",
+            "t": "hello",
+            "b": true,
+            "u": "/a/{id}",
+            "l": [0],
+        })
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn example_operators() -> anyhow::Result<()> {
+    let code = r#"
+        let @b = { 'x int };
+        let @a = { 'a str } & { 'b @b };
+        let @c = int | str;
+        res / ( get -> @a, put -> @c );
+    "#;
+
+    assert_eq!(
+        example(code, "@a")?,
+        json!({ "a": "string", "b": { "x": 0 } })
+    );
+    assert_eq!(example(code, "@c")?, json!(0));
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn example_patterns() -> anyhow::Result<()> {
+    let code = r#"
+        let @a = {
+          's str `pattern: "^[a-z]{2,4}-[0-9]+$"`
+        , 't str `pattern: "^(red|green)$"`
+        };
+        res / ( get -> @a );
+    "#;
+
+    assert_eq!(example(code, "@a")?, json!({ "s": "aa-0", "t": "red" }));
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn example_seeded() -> anyhow::Result<()> {
+    let code = r#"
+        let @a = {
+          'n num `minimum: 1.5, maximum: 9, multipleOf: 0.5`
+        , 'm num `maximum: -3`
+        , 'i int `minimum: -7, maximum: 20, multipleOf: 4`
+        , 's str `enum: [x, y, z]`
+        , 'p str `pattern: "^[A-Z]{3}-\\d{2,}$"`
+        , 'b bool
+        , 'l [{ 'x int `minimum: 1` }]
+        , 'v (int | str)
+        };
+        let @b = @a & { 'c str `example: "given"` };
+        res / ( get -> @b );
+    "#;
+
+    let spec = eval(code)?;
+    let Reference::Schema(schema) = &spec.refs[&Ident::from("@b")];
+    let validator = oal_validate::Validator::new(&spec.refs);
+
+    for seed in 0..50 {
+        let value = Synthesizer::new(&spec.refs).with_seed(seed).schema(schema);
+        let errors = validator.schema(schema, &value, "");
+        assert!(
+            errors.is_empty(),
+            "seed {}: {} in {}",
+            seed,
+            errors[0],
+            value
+        );
+        assert_eq!(value["c"], "given");
+    }
+
+    let first = Synthesizer::new(&spec.refs).with_seed(42).schema(schema);
+    let second = Synthesizer::new(&spec.refs).with_seed(42).schema(schema);
+    assert_eq!(first, second, "the same seed should give the same example");
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn example_wide_bounds() -> anyhow::Result<()> {
+    let code = r#"
+        let @a = {
+          'i int `minimum: -9223372036854775808, maximum: 9223372036854775807`
+        , 'j int `minimum: -9223372036854775808, maximum: 9223372036854775807, multipleOf: 3`
+        , 'k int `maximum: -9223372036854775807, multipleOf: 2`
+        , 'l int `minimum: 9223372036854775800, multipleOf: 1000`
+        };
+        res / ( get -> @a );
+    "#;
+
+    let spec = eval(code)?;
+    let Reference::Schema(schema) = &spec.refs[&Ident::from("@a")];
+    let validator = oal_validate::Validator::new(&spec.refs);
+
+    let value = Synthesizer::new(&spec.refs).schema(schema);
+    assert_eq!(value["i"], i64::MIN);
+    for seed in 0..20 {
+        let value = Synthesizer::new(&spec.refs).with_seed(seed).schema(schema);
+        assert!(value["j"].as_i64().unwrap() % 3 == 0);
+        assert!(value["k"].as_i64().unwrap() % 2 == 0);
+        assert!(validator.schema(schema, &value, "")[..]
+            .iter()
+            .all(|e| e.pointer == "/l"));
+    }
+
+    anyhow::Ok(())
+}
@@ -0,0 +1,34 @@
+use crate::Builder;
+use oal_compiler::spec;
+use openapiv3::{Components, Operation, Parameter, PathItem, ReferenceOr, Responses, Schema};
+use serde::Serialize;
+
+/// A piece of OpenAPI definition generated for a single expression.
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum Fragment {
+    Schema(Box<ReferenceOr<Schema>>),
+    Parameter(Box<Parameter>),
+    Responses(Box<Responses>),
+    Operation(Box<Operation>),
+    PathItem(Box<PathItem>),
+}
+
+impl Builder {
+    /// Generates the definition of a single expression, along with the components it uses.
+    ///
+    /// Properties are generated as query parameters,
+    /// contents and ranges as responses and transfers as operations.
+    pub fn into_fragment(self, fragment: &spec::Fragment) -> (Fragment, Components) {
+        let generated = match fragment {
+            spec::Fragment::Schema(s) => Fragment::Schema(Box::new(self.schema(s))),
+            spec::Fragment::Property(p) => Fragment::Parameter(Box::new(self.prop_query_param(p))),
+            spec::Fragment::Ranges(r) => Fragment::Responses(Box::new(self.ranges_responses(r))),
+            spec::Fragment::Transfer(x) => {
+                Fragment::Operation(Box::new(self.xfer_operation(x, x.id.clone())))
+            }
+            spec::Fragment::Relation(r) => Fragment::PathItem(Box::new(self.relation_path_item(r))),
+        };
+        (generated, self.all_components())
+    }
+}
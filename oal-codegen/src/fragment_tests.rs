@@ -0,0 +1,79 @@
+use crate::tests::compile;
+use crate::Builder;
+use oal_compiler::spec::Spec;
+use serde_json::{json, Value};
+
+fn fragment(code: &str, name: &str) -> anyhow::Result<(Value, Value)> {
+    let mods = compile(code)?;
+    let (frag, spec) = Spec::from_decl(&mods, &mods.base, &name.into())?;
+    let (generated, components) = Builder::new().with_spec(spec).into_fragment(&frag);
+    anyhow::Ok((
+        serde_json::to_value(generated)?,
+        serde_json::to_value(components.schemas)?,
+    ))
+}
+
+#[test]
+fn fragment_schema() -> anyhow::Result<()> {
+    let code = r#"
+        let @a = { 'b bool };
+        let @c = { 'a @a, 'n num };
+    "#;
+
+    let (schema, components) = fragment(code, "@c")?;
+
+    assert_eq!(
+        schema,
+        json!({
+            "type": "object",
+            "properties": {
+                "a": { "$ref": "#/components/schemas/a" },
+                "n": { "type": "number" },
+            },
+        })
+    );
+    assert_eq!(
+        components,
+        json!({ "a": { "type": "object", "properties": { "b": { "type": "boolean" } } } })
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn fragment_ranges() -> anyhow::Result<()> {
+    let code = r#"
+        let with_err s = <status=200, s> `description: "ok"` :: <status=404,> `description: "none"`;
+        let r = with_err str;
+    "#;
+
+    let (responses, _) = fragment(code, "r")?;
+
+    assert_eq!(
+        responses,
+        json!({
+            "200": {
+                "description": "ok",
+                "content": { "application/json": { "schema": { "type": "string" } } },
+            },
+            "404": { "description": "none" },
+        })
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn fragment_transfer() -> anyhow::Result<()> {
+    let code = r#"
+        let x = get { 'q str } -> <{}> `description: "ok"`;
+    "#;
+
+    let (operation, _) = fragment(code, "x")?;
+
+    assert_eq!(operation["parameters"][0]["name"], "q");
+    assert_eq!(operation["parameters"][0]["in"], "query");
+    assert_eq!(operation["responses"]["default"]["description"], "ok");
+
+    anyhow::Ok(())
+}
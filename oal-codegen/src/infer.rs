@@ -0,0 +1,165 @@
+use crate::decompile::decompile;
+use crate::errors::Result;
+use indexmap::{indexmap, IndexMap};
+use openapiv3::{
+    ArrayType, Components, IntegerType, NumberType, ObjectType, OpenAPI, ReferenceOr, Schema,
+    SchemaKind, StringType, Type,
+};
+use serde_json::Value;
+
+/// The shape of sample values, merged across samples.
+#[derive(Default)]
+struct Shape {
+    /// The number of samples, null values excluded
+    count: usize,
+    boolean: bool,
+    integer: bool,
+    number: bool,
+    string: bool,
+    /// The shape of the items of all array samples
+    items: Option<Box<Shape>>,
+    /// The shape of the properties of all object samples
+    props: Option<IndexMap<String, Shape>>,
+    /// The number of object samples
+    objects: usize,
+}
+
+fn schema_of(kind: SchemaKind) -> Schema {
+    Schema {
+        schema_data: Default::default(),
+        schema_kind: kind,
+    }
+}
+
+impl Shape {
+    /// Merges a sample value into the shape.
+    ///
+    /// Null values are not counted, so that a property always null or missing is optional.
+    fn add(&mut self, value: &Value) {
+        match value {
+            Value::Null => return,
+            Value::Bool(_) => self.boolean = true,
+            Value::Number(n) if n.is_i64() || n.is_u64() => self.integer = true,
+            Value::Number(_) => self.number = true,
+            Value::String(_) => self.string = true,
+            Value::Array(items) => {
+                let shape = self.items.get_or_insert_with(Default::default);
+                items.iter().for_each(|item| shape.add(item));
+            }
+            Value::Object(obj) => {
+                self.objects += 1;
+                let props = self.props.get_or_insert_with(Default::default);
+                for (name, value) in obj.iter() {
+                    props.entry(name.clone()).or_default().add(value);
+                }
+            }
+        }
+        self.count += 1;
+    }
+
+    /// Returns the object type of the shape.
+    ///
+    /// Properties only ever null are omitted, as there is no schema for null values,
+    /// and their path is added to the omitted ones.
+    fn object_type(
+        &self,
+        props: &IndexMap<String, Shape>,
+        path: &str,
+        omitted: &mut Vec<String>,
+    ) -> Type {
+        let mut properties = IndexMap::new();
+        for (name, shape) in props.iter() {
+            let path = format!("{}/{}", path, name);
+            if shape.count == 0 {
+                omitted.push(path);
+            } else {
+                let schema = shape.schema(&path, omitted);
+                properties.insert(name.clone(), ReferenceOr::boxed_item(schema));
+            }
+        }
+        let required = props
+            .iter()
+            .filter(|(_, shape)| shape.count > 0 && shape.count == self.objects)
+            .map(|(name, _)| name.clone())
+            .collect();
+        Type::Object(ObjectType {
+            properties,
+            required,
+            ..Default::default()
+        })
+    }
+
+    /// Returns the schema of the shape, with conflicting types as alternatives.
+    ///
+    /// Integers are numbers if any sample is not an integer.
+    fn schema(&self, path: &str, omitted: &mut Vec<String>) -> Schema {
+        let mut types = Vec::new();
+        if self.boolean {
+            types.push(Type::Boolean {});
+        }
+        if self.number {
+            types.push(Type::Number(NumberType::default()));
+        } else if self.integer {
+            types.push(Type::Integer(IntegerType::default()));
+        }
+        if self.string {
+            types.push(Type::String(StringType::default()));
+        }
+        if let Some(items) = &self.items {
+            types.push(Type::Array(ArrayType {
+                items: Some(ReferenceOr::boxed_item(
+                    items.schema(&format!("{}/*", path), omitted),
+                )),
+                min_items: None,
+                max_items: None,
+                unique_items: false,
+            }));
+        }
+        if let Some(props) = &self.props {
+            types.push(self.object_type(props, path, omitted));
+        }
+        let mut schemas: Vec<_> = types
+            .into_iter()
+            .map(|t| schema_of(SchemaKind::Type(t)))
+            .collect();
+        match schemas.len() {
+            0 => schema_of(SchemaKind::Any(Default::default())),
+            1 => schemas.remove(0),
+            _ => schema_of(SchemaKind::OneOf {
+                one_of: schemas.into_iter().map(ReferenceOr::Item).collect(),
+            }),
+        }
+    }
+}
+
+/// Infers a schema from sample JSON values.
+///
+/// Samples are merged: properties missing from some object samples are optional,
+/// array items are merged across arrays, and conflicting types become alternatives.
+/// Properties only ever null are omitted, and returned by path, like `/lines/*/note`.
+pub fn infer_schema<'a, I: IntoIterator<Item = &'a Value>>(samples: I) -> (Schema, Vec<String>) {
+    let mut shape = Shape::default();
+    samples.into_iter().for_each(|s| shape.add(s));
+    let mut omitted = Vec::new();
+    let schema = shape.schema("", &mut omitted);
+    (schema, omitted)
+}
+
+/// Infers the declaration of a reference from sample JSON values, as the source of a program.
+///
+/// Returns the paths of the properties omitted for being only ever null along with the source.
+pub fn infer_declaration<'a, I: IntoIterator<Item = &'a Value>>(
+    name: &str,
+    samples: I,
+) -> Result<(String, Vec<String>)> {
+    let (schema, omitted) = infer_schema(samples);
+    let name = name.strip_prefix('@').unwrap_or(name).to_owned();
+    let api = OpenAPI {
+        components: Some(Components {
+            schemas: indexmap! { name => ReferenceOr::Item(schema) },
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    Ok((decompile(&api)?, omitted))
+}
@@ -0,0 +1,64 @@
+use crate::infer_declaration;
+use crate::tests::eval;
+use oal_compiler::spec::Reference;
+use oal_syntax::atom::Ident;
+use serde_json::{json, Value};
+
+#[test]
+fn infer_merge() -> anyhow::Result<()> {
+    let samples = [
+        json!({
+            "id": 1,
+            "total": 10,
+            "status": "paid",
+            "lines": [{ "sku": "a", "qty": 2, "gift": null }],
+            "note": null
+        }),
+        json!({
+            "id": 2,
+            "total": 12.5,
+            "status": 3,
+            "lines": [{ "sku": "b" }, { "sku": "c", "qty": 1 }],
+            "coupon": true
+        }),
+    ];
+
+    let (code, omitted) = infer_declaration("@Order", samples.iter())?;
+
+    assert_eq!(
+        code.trim_end(),
+        "let @Order = { \
+         'id int `required: true`, \
+         'lines [{ 'qty int, 'sku str `required: true` }] `required: true`, \
+         'status (int | str) `required: true`, \
+         'total num `required: true`, \
+         'coupon bool \
+         };"
+    );
+
+    assert_eq!(omitted, vec!["/lines/*/gift", "/note"]);
+
+    // The samples conform to the declaration inferred from them.
+    let spec = eval(&format!("{}\nres / ( get -> @Order );", code))?;
+    let schema = match spec.refs.get(&Ident::from("@Order")) {
+        Some(Reference::Schema(schema)) => schema,
+        _ => panic!("expected schema"),
+    };
+    let validator = oal_validate::Validator::new(&spec.refs);
+    for sample in samples.iter() {
+        assert_eq!(validator.schema(schema, sample, ""), vec![]);
+    }
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn infer_empty() -> anyhow::Result<()> {
+    let samples: [Value; 0] = [];
+
+    let (code, _) = infer_declaration("Empty", samples.iter())?;
+
+    assert_eq!(code.trim_end(), "let @Empty = {};");
+
+    anyhow::Ok(())
+}
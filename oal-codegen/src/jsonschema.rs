@@ -0,0 +1,194 @@
+use indexmap::IndexMap;
+use oal_compiler::spec;
+use oal_syntax::{ast, atom};
+use serde_json::{json, Map, Value};
+
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// How references between schemas are generated.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Layout {
+    /// All schemas in one document under `$defs`
+    Bundle,
+    /// One document per schema, side by side
+    Files,
+}
+
+/// A generator of JSON Schema (draft 2020-12) documents for the references of a specification.
+pub struct SchemaBuilder {
+    spec: spec::Spec,
+}
+
+impl SchemaBuilder {
+    pub fn new(spec: spec::Spec) -> Self {
+        SchemaBuilder { spec }
+    }
+
+    /// Returns the name of the file generated for a reference.
+    pub fn file_name(name: &atom::Ident) -> String {
+        format!("{}.json", name.untagged())
+    }
+
+    /// Generates a single document with all the references under `$defs`.
+    pub fn into_bundle(self) -> Value {
+        let defs: Map<String, Value> = self
+            .spec
+            .refs
+            .iter()
+            .map(|(name, r)| (name.untagged(), self.reference(r, Layout::Bundle)))
+            .collect();
+        json!({
+            "$schema": DIALECT,
+            "$defs": defs,
+        })
+    }
+
+    /// Generates one standalone document per reference, indexed by file name.
+    pub fn into_files(self) -> IndexMap<String, Value> {
+        self.spec
+            .refs
+            .iter()
+            .map(|(name, r)| {
+                let mut doc = Map::new();
+                doc.insert("$schema".into(), DIALECT.into());
+                doc.insert("$id".into(), Self::file_name(name).into());
+                if let Value::Object(s) = self.reference(r, Layout::Files) {
+                    doc.extend(s);
+                }
+                (Self::file_name(name), Value::Object(doc))
+            })
+            .collect()
+    }
+
+    fn reference(&self, r: &spec::Reference, layout: Layout) -> Value {
+        match r {
+            spec::Reference::Schema(s) => self.schema(s, layout),
+        }
+    }
+
+    fn number_schema(&self, p: &spec::PrimNumber) -> Map<String, Value> {
+        let mut m = Map::new();
+        m.insert("type".into(), "number".into());
+        insert_opt(&mut m, "minimum", p.minimum);
+        insert_opt(&mut m, "maximum", p.maximum);
+        insert_opt(&mut m, "multipleOf", p.multiple_of);
+        insert_example(&mut m, p.example);
+        m
+    }
+
+    fn integer_schema(&self, p: &spec::PrimInteger) -> Map<String, Value> {
+        let mut m = Map::new();
+        m.insert("type".into(), "integer".into());
+        insert_opt(&mut m, "minimum", p.minimum);
+        insert_opt(&mut m, "maximum", p.maximum);
+        insert_opt(&mut m, "multipleOf", p.multiple_of);
+        insert_example(&mut m, p.example);
+        m
+    }
+
+    fn string_schema(&self, p: &spec::PrimString) -> Map<String, Value> {
+        let mut m = Map::new();
+        m.insert("type".into(), "string".into());
+        insert_opt(&mut m, "pattern", p.pattern.clone());
+        if !p.enumeration.is_empty() {
+            m.insert("enum".into(), p.enumeration.clone().into());
+        }
+        insert_example(&mut m, p.example.clone());
+        m
+    }
+
+    fn boolean_schema(&self, _: &spec::PrimBoolean) -> Map<String, Value> {
+        let mut m = Map::new();
+        m.insert("type".into(), "boolean".into());
+        m
+    }
+
+    fn uri_schema(&self, uri: &spec::Uri) -> Map<String, Value> {
+        let mut m = Map::new();
+        m.insert("type".into(), "string".into());
+        m.insert("format".into(), "uri-reference".into());
+        insert_example(&mut m, uri.example.clone());
+        m
+    }
+
+    fn object_schema(&self, obj: &spec::Object, layout: Layout) -> Map<String, Value> {
+        let properties: Map<String, Value> = obj
+            .props
+            .iter()
+            .map(|p| (p.name.as_ref().to_owned(), self.schema(&p.schema, layout)))
+            .collect();
+        let required: Vec<Value> = obj
+            .props
+            .iter()
+            .filter(|p| p.schema.required.unwrap_or(false))
+            .map(|p| p.name.as_ref().into())
+            .collect();
+        let mut m = Map::new();
+        m.insert("type".into(), "object".into());
+        m.insert("properties".into(), properties.into());
+        if !required.is_empty() {
+            m.insert("required".into(), required.into());
+        }
+        m
+    }
+
+    fn array_schema(&self, array: &spec::Array, layout: Layout) -> Map<String, Value> {
+        let mut m = Map::new();
+        m.insert("type".into(), "array".into());
+        m.insert("items".into(), self.schema(&array.item, layout));
+        m
+    }
+
+    fn op_schema(&self, op: &spec::VariadicOp, layout: Layout) -> Map<String, Value> {
+        let keyword = match op.op {
+            ast::Operator::Join => "allOf",
+            ast::Operator::Sum => "oneOf",
+            ast::Operator::Any => "anyOf",
+            ast::Operator::Range => unreachable!(),
+        };
+        let schemas: Vec<Value> = op.schemas.iter().map(|s| self.schema(s, layout)).collect();
+        let mut m = Map::new();
+        m.insert(keyword.into(), schemas.into());
+        m
+    }
+
+    fn reference_schema(&self, name: &atom::Ident, layout: Layout) -> Map<String, Value> {
+        let reference = match layout {
+            Layout::Bundle => format!("#/$defs/{}", name.untagged()),
+            Layout::Files => Self::file_name(name),
+        };
+        let mut m = Map::new();
+        m.insert("$ref".into(), reference.into());
+        m
+    }
+
+    fn schema(&self, s: &spec::Schema, layout: Layout) -> Value {
+        let mut m = match &s.expr {
+            spec::SchemaExpr::Num(p) => self.number_schema(p),
+            spec::SchemaExpr::Str(p) => self.string_schema(p),
+            spec::SchemaExpr::Bool(p) => self.boolean_schema(p),
+            spec::SchemaExpr::Int(p) => self.integer_schema(p),
+            spec::SchemaExpr::Rel(rel) => self.uri_schema(&rel.uri),
+            spec::SchemaExpr::Uri(uri) => self.uri_schema(uri),
+            spec::SchemaExpr::Object(obj) => self.object_schema(obj, layout),
+            spec::SchemaExpr::Array(array) => self.array_schema(array, layout),
+            spec::SchemaExpr::Op(op) => self.op_schema(op, layout),
+            spec::SchemaExpr::Ref(name) => self.reference_schema(name, layout),
+        };
+        insert_opt(&mut m, "title", s.title.clone());
+        insert_opt(&mut m, "description", s.desc.clone());
+        Value::Object(m)
+    }
+}
+
+fn insert_opt<T: Into<Value>>(m: &mut Map<String, Value>, key: &str, val: Option<T>) {
+    if let Some(v) = val {
+        m.insert(key.to_owned(), v.into());
+    }
+}
+
+fn insert_example<T: Into<Value>>(m: &mut Map<String, Value>, example: Option<T>) {
+    if let Some(e) = example {
+        m.insert("examples".to_owned(), Value::Array(vec![e.into()]));
+    }
+}
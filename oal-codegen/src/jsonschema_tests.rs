@@ -0,0 +1,55 @@
+use crate::tests::eval;
+use crate::SchemaBuilder;
+use serde_json::json;
+
+#[test]
+fn jsonschema_bundle() -> anyhow::Result<()> {
+    let spec = eval(
+        r#"
+        let @b = int `minimum: 1, maximum: 9`;
+        let @a = { 'b @b `required: true`, 'c [str `pattern: "^x"`] };
+        res / ( get -> @a );
+    "#,
+    )?;
+
+    let bundle = SchemaBuilder::new(spec).into_bundle();
+
+    assert_eq!(
+        bundle["$schema"],
+        "https://json-schema.org/draft/2020-12/schema"
+    );
+    assert_eq!(
+        bundle["$defs"]["b"],
+        json!({ "type": "integer", "minimum": 1, "maximum": 9 })
+    );
+    assert_eq!(
+        bundle["$defs"]["a"],
+        json!({
+            "type": "object",
+            "properties": {
+                "b": { "$ref": "#/$defs/b" },
+                "c": { "type": "array", "items": { "type": "string", "pattern": "^x" } },
+            },
+            "required": ["b"],
+        })
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn jsonschema_files() -> anyhow::Result<()> {
+    let spec = eval("let @b = num; let @a = @b | bool; res / ( get -> @a );")?;
+
+    let files = SchemaBuilder::new(spec).into_files();
+
+    assert_eq!(files.len(), 2);
+    assert_eq!(files["b.json"]["$id"], "b.json");
+    assert_eq!(files["b.json"]["type"], "number");
+    assert_eq!(
+        files["a.json"]["oneOf"],
+        json!([{ "$ref": "b.json" }, { "type": "boolean" }])
+    );
+
+    anyhow::Ok(())
+}
@@ -1,8 +1,54 @@
+mod decompile;
+pub mod errors;
+mod example;
+mod fragment;
+mod infer;
+mod jsonschema;
 mod oas;
-
+mod overlay;
+mod rust;
+mod sourcemap;
+mod validate;
+
+#[cfg(test)]
+mod builder_tests;
+#[cfg(test)]
+mod decompile_tests;
+#[cfg(test)]
+mod example_tests;
+#[cfg(test)]
+mod fragment_tests;
+#[cfg(test)]
+mod infer_tests;
+#[cfg(test)]
+mod jsonschema_tests;
+#[cfg(test)]
+mod module_tests;
+#[cfg(test)]
+mod overlay_tests;
+#[cfg(test)]
+mod rust_tests;
+#[cfg(test)]
+mod sourcemap_tests;
+#[cfg(test)]
+mod tests;
+#[cfg(test)]
+mod validate_tests;
+
+pub use crate::decompile::{decompile, decompile_module};
+pub use crate::example::Synthesizer;
+pub use crate::fragment::Fragment;
+pub use crate::infer::{infer_declaration, infer_schema};
+pub use crate::jsonschema::SchemaBuilder;
+pub use crate::overlay::Overlay;
+pub use crate::rust::RustBuilder;
+pub use crate::sourcemap::{SourceMap, SOURCE_EXTENSION};
+pub use crate::validate::{validate, Issue};
+
+use crate::errors::{Error, Kind, Result};
 use crate::oas::into_box_ref;
 use indexmap::{indexmap, IndexMap};
-use oal_compiler::spec;
+use oal_compiler::{spec, Locator};
 use oal_syntax::atom::HttpStatusRange;
 use oal_syntax::{ast, atom};
 use openapiv3::{
@@ -11,12 +57,147 @@ use openapiv3::{
     RequestBody, Response, Responses, Schema, SchemaData, SchemaKind, Server, StatusCode,
     StringType, Type, VariantOrUnknownOrEmpty,
 };
+use serde_json::Value;
+use std::cell::RefCell;
 use std::iter::once;
 
+/// The module being generated when splitting the output by module.
+#[derive(Clone)]
+struct ModuleScope {
+    main: Locator,
+    /// The path of the definition generated for each module
+    paths: IndexMap<Locator, String>,
+    current: Locator,
+}
+
 #[derive(Default)]
 pub struct Builder {
     spec: Option<spec::Spec>,
     base: Option<OpenAPI>,
+    scope: Option<ModuleScope>,
+    dereference: bool,
+    /// The seed of the examples synthesised where none is given, if enabled
+    examples: Option<u64>,
+    /// The references being inlined, from the outermost to the innermost
+    expanding: RefCell<Vec<atom::Ident>>,
+    /// The recursive references kept as references while inlining
+    recursive: RefCell<Vec<atom::Ident>>,
+}
+
+/// Returns the path of the definition generated for each module, relative to the output directory.
+///
+/// Paths are relative to the deepest directory containing all the modules, so that no definition
+/// is written outside of the output directory. Modules on another host or with another scheme
+/// than the main module are generated at the root, under their file name.
+pub fn module_paths(main: &Locator, modules: &[Locator]) -> Result<IndexMap<Locator, String>> {
+    let segments = |loc: &Locator| -> Vec<String> {
+        match loc.url.path_segments() {
+            Some(s) => s.map(ToOwned::to_owned).collect(),
+            None => vec![loc.url.path().to_owned()],
+        }
+    };
+    let is_near = |loc: &Locator| {
+        loc.url.scheme() == main.url.scheme()
+            && loc.url.host() == main.url.host()
+            && loc.url.port() == main.url.port()
+    };
+    let mut root = segments(main);
+    root.pop();
+    for loc in modules.iter().filter(|l| is_near(l)) {
+        let dirs = segments(loc);
+        let common = root
+            .iter()
+            .zip(dirs[..dirs.len() - 1].iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        root.truncate(common);
+    }
+
+    let mut paths: IndexMap<Locator, String> = IndexMap::new();
+    for loc in modules {
+        let segments = segments(loc);
+        let path = if is_near(loc) {
+            segments[root.len()..].join("/")
+        } else {
+            segments.last().cloned().unwrap_or_default()
+        };
+        let stem = [".oal", ".yaml", ".yml", ".json"]
+            .iter()
+            .find_map(|ext| path.strip_suffix(ext))
+            .unwrap_or(&path);
+        let path = format!("{}.yaml", stem);
+        if let Some((other, _)) = paths.iter().find(|(_, p)| **p == path) {
+            return Err(
+                Error::new(Kind::Conflict, "modules generated at the same path")
+                    .with(&path)
+                    .with(other)
+                    .with(loc),
+            );
+        }
+        paths.insert(loc.clone(), path);
+    }
+    Ok(paths)
+}
+
+/// Tells whether a schema has an example given by the user, following references.
+fn has_example(refs: &spec::References, s: &spec::Schema) -> bool {
+    let mut schema = s;
+    let mut followed = Vec::new();
+    while let spec::SchemaExpr::Ref(name) = &schema.expr {
+        match refs.get(name) {
+            Some(spec::Reference::Schema(referenced)) if !followed.contains(&name) => {
+                followed.push(name);
+                schema = referenced;
+            }
+            _ => return false,
+        }
+    }
+    match &schema.expr {
+        spec::SchemaExpr::Num(p) => p.example.is_some(),
+        spec::SchemaExpr::Str(p) => p.example.is_some(),
+        spec::SchemaExpr::Int(p) => p.example.is_some(),
+        spec::SchemaExpr::Uri(uri) => uri.example.is_some(),
+        spec::SchemaExpr::Rel(rel) => rel.uri.example.is_some(),
+        _ => false,
+    }
+}
+
+/// Returns the relative reference from the definition generated for a module to another.
+fn module_reference(from: &str, to: &str) -> String {
+    let from: Vec<_> = from.split('/').collect();
+    let to: Vec<_> = to.split('/').collect();
+    let dirs = &from[..from.len() - 1];
+    let common = dirs
+        .iter()
+        .zip(to.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut segments = vec![".."; dirs.len() - common];
+    segments.extend(&to[common..]);
+    segments.join("/")
+}
+
+/// Returns the description of a response without one, as OpenAPI requires it.
+fn default_description(status: Option<&atom::HttpStatus>) -> &'static str {
+    let range = match status {
+        Some(atom::HttpStatus::Range(range)) => Some(*range),
+        Some(atom::HttpStatus::Code(code)) => match code.get() / 100 {
+            1 => Some(HttpStatusRange::Info),
+            2 => Some(HttpStatusRange::Success),
+            3 => Some(HttpStatusRange::Redirect),
+            4 => Some(HttpStatusRange::ClientError),
+            _ => Some(HttpStatusRange::ServerError),
+        },
+        None => None,
+    };
+    match range {
+        Some(HttpStatusRange::Info) => "Informational response",
+        Some(HttpStatusRange::Success) => "Successful response",
+        Some(HttpStatusRange::Redirect) => "Redirection",
+        Some(HttpStatusRange::ClientError) => "Client error",
+        Some(HttpStatusRange::ServerError) => "Server error",
+        None => "Default response",
+    }
 }
 
 type Headers = IndexMap<String, ReferenceOr<Header>>;
@@ -36,6 +217,61 @@ impl Builder {
         self
     }
 
+    /// Inlines referenced schemas instead of generating components.
+    ///
+    /// Recursive references cannot be inlined and are kept as references to components.
+    pub fn with_dereference(mut self, dereference: bool) -> Self {
+        self.dereference = dereference;
+        self
+    }
+
+    /// Synthesises examples for media types, parameters, headers and schema components
+    /// which have none, from the given seed so that the output is stable.
+    pub fn with_examples(mut self, seed: Option<u64>) -> Self {
+        self.examples = seed;
+        self
+    }
+
+    /// Generates one OpenAPI definition per module declaring exported references,
+    /// along with its path relative to the output directory as given by [`module_paths`].
+    ///
+    /// Paths are only generated in the definition of the main module.
+    /// References to schemas declared in other modules become relative external references.
+    pub fn into_modules(self, main: &Locator) -> Result<IndexMap<Locator, (String, OpenAPI)>> {
+        let mut modules = vec![main.clone()];
+        if let Some(spec) = &self.spec {
+            for source in spec.origins.values() {
+                if !modules.contains(&source.loc) {
+                    modules.push(source.loc.clone());
+                }
+            }
+        }
+        let paths = module_paths(main, &modules)?;
+        let definitions = paths
+            .iter()
+            .map(|(current, path)| {
+                let builder = Builder {
+                    spec: self.spec.clone(),
+                    base: if current == main {
+                        self.base.clone()
+                    } else {
+                        None
+                    },
+                    scope: Some(ModuleScope {
+                        main: main.clone(),
+                        paths: paths.clone(),
+                        current: current.clone(),
+                    }),
+                    dereference: self.dereference,
+                    examples: self.examples,
+                    ..Default::default()
+                };
+                (current.clone(), (path.clone(), builder.into_openapi()))
+            })
+            .collect();
+        Ok(definitions)
+    }
+
     pub fn into_openapi(self) -> OpenAPI {
         let paths = self.all_paths();
         let components = self.all_components();
@@ -233,10 +469,42 @@ impl Builder {
         }
     }
 
+    /// Inlines the schema of a reference unless it is recursive.
+    fn inline_schema(&self, name: &atom::Ident) -> Option<Schema> {
+        let referenced = match self.spec.as_ref().and_then(|spec| spec.refs.get(name)) {
+            Some(spec::Reference::Schema(s)) => s,
+            None => return None,
+        };
+        if self.expanding.borrow().contains(name) {
+            if !self.recursive.borrow().contains(name) {
+                self.recursive.borrow_mut().push(name.clone());
+            }
+            return None;
+        }
+        self.expanding.borrow_mut().push(name.clone());
+        let schema = self.schema(referenced);
+        self.expanding.borrow_mut().pop();
+        match schema {
+            ReferenceOr::Item(sch) => Some(sch),
+            ReferenceOr::Reference { .. } => None,
+        }
+    }
+
     fn schema(&self, s: &spec::Schema) -> ReferenceOr<Schema> {
         if let spec::SchemaExpr::Ref(name) = &s.expr {
-            ReferenceOr::Reference {
-                reference: format!("#/components/schemas/{}", name.untagged()),
+            match self.dereference.then(|| self.inline_schema(name)).flatten() {
+                Some(mut sch) => {
+                    if s.desc.is_some() {
+                        sch.schema_data.description = s.desc.clone();
+                    }
+                    if s.title.is_some() {
+                        sch.schema_data.title = s.title.clone();
+                    }
+                    ReferenceOr::Item(sch)
+                }
+                None => ReferenceOr::Reference {
+                    reference: self.schema_reference(name),
+                },
             }
         } else {
             let mut sch = match &s.expr {
@@ -262,14 +530,66 @@ impl Builder {
         }
     }
 
+    /// Synthesises an example for a schema if enabled, unless the user has given one.
+    fn example(&self, s: &spec::Schema) -> Option<Value> {
+        let seed = self.examples?;
+        let spec = self.spec.as_ref()?;
+        if has_example(&spec.refs, s) {
+            return None;
+        }
+        Some(Synthesizer::new(&spec.refs).with_seed(seed).schema(s))
+    }
+
+    /// Generates a schema component, with an example if enabled.
+    fn component(&self, s: &spec::Schema) -> ReferenceOr<Schema> {
+        let mut generated = self.schema(s);
+        if let (ReferenceOr::Item(sch), Some(example)) = (&mut generated, self.example(s)) {
+            sch.schema_data.example = Some(example);
+        }
+        generated
+    }
+
+    /// Returns the module declaring the given reference, if splitting the output by module.
+    fn origin(&self, name: &atom::Ident) -> Option<&Locator> {
+        self.scope.as_ref().map(|scope| {
+            self.spec
+                .as_ref()
+                .and_then(|spec| spec.origins.get(name).map(|s| &s.loc))
+                .unwrap_or(&scope.main)
+        })
+    }
+
+    /// Tells whether the given reference belongs to the module being generated.
+    fn is_local(&self, name: &atom::Ident) -> bool {
+        match (&self.scope, self.origin(name)) {
+            (Some(scope), Some(origin)) => *origin == scope.current,
+            _ => true,
+        }
+    }
+
+    fn schema_reference(&self, name: &atom::Ident) -> String {
+        let local = format!("#/components/schemas/{}", name.untagged());
+        match (&self.scope, self.origin(name)) {
+            (Some(scope), Some(origin)) if *origin != scope.current => {
+                format!(
+                    "{}{}",
+                    module_reference(&scope.paths[&scope.current], &scope.paths[origin]),
+                    local
+                )
+            }
+            _ => local,
+        }
+    }
+
     fn prop_param_data(&self, prop: &spec::Property, required: bool) -> ParameterData {
+        let schema = self.schema(&prop.schema);
         ParameterData {
             name: prop.name.as_ref().into(),
             description: prop.desc.clone(),
             required,
             deprecated: None,
-            format: ParameterSchemaOrContent::Schema(self.schema(&prop.schema)),
-            example: None,
+            example: self.example(&prop.schema),
+            format: ParameterSchemaOrContent::Schema(schema),
             examples: Default::default(),
             explode: None,
             extensions: Default::default(),
@@ -300,13 +620,14 @@ impl Builder {
     }
 
     fn prop_header(&self, prop: &spec::Property) -> Header {
+        let schema = self.schema(&prop.schema);
         Header {
             description: prop.desc.clone(),
             style: Default::default(),
             required: prop.required.unwrap_or(false),
             deprecated: None,
-            format: ParameterSchemaOrContent::Schema(self.schema(&prop.schema)),
-            example: None,
+            example: self.example(&prop.schema),
+            format: ParameterSchemaOrContent::Schema(schema),
             examples: Default::default(),
             extensions: Default::default(),
         }
@@ -345,16 +666,23 @@ impl Builder {
         let media = domain.media.clone().unwrap_or_else(|| self.media_type());
         domain.schema.as_ref().map(|schema| {
             ReferenceOr::Item(RequestBody {
-                content: indexmap! { media => MediaType {
-                    schema: Some(self.schema(schema)),
-                    ..Default::default()
-                }},
+                content: indexmap! { media => self.media_schema(schema) },
                 description: domain.desc.clone(),
                 ..Default::default()
             })
         })
     }
 
+    /// Generates the media type of a content schema, with an example if enabled.
+    fn media_schema(&self, s: &spec::Schema) -> MediaType {
+        let schema = self.schema(s);
+        MediaType {
+            example: self.example(s),
+            schema: Some(schema),
+            ..Default::default()
+        }
+    }
+
     fn xfer_request(&self, xfer: &spec::Transfer) -> Option<ReferenceOr<RequestBody>> {
         self.domain_request(&xfer.domain)
     }
@@ -387,10 +715,14 @@ impl Builder {
     }
 
     fn xfer_responses(&self, xfer: &spec::Transfer) -> Responses {
+        self.ranges_responses(&xfer.ranges)
+    }
+
+    fn ranges_responses(&self, ranges: &spec::Ranges) -> Responses {
         let mut default = None;
         let mut responses = IndexMap::new();
 
-        for ((status, media), content) in xfer.ranges.iter() {
+        for ((status, media), content) in ranges.iter() {
             let response = if let Some(s) = status {
                 responses
                     .entry(self.http_status_code(s))
@@ -401,14 +733,13 @@ impl Builder {
             if let ReferenceOr::Item(res) = response {
                 if let Some(schema) = content.schema.as_ref() {
                     let media_type = media.clone().unwrap_or_else(|| self.media_type());
-                    let media_schema = MediaType {
-                        schema: Some(self.schema(schema)),
-                        ..Default::default()
-                    };
-                    res.content.insert(media_type, media_schema);
+                    res.content.insert(media_type, self.media_schema(schema));
                 }
                 res.headers = self.content_headers(content);
-                res.description = content.desc.clone().unwrap_or_else(|| "".to_owned());
+                res.description = content
+                    .desc
+                    .clone()
+                    .unwrap_or_else(|| default_description(status.as_ref()).to_owned());
             } else {
                 unreachable!();
             }
@@ -463,6 +794,26 @@ impl Builder {
         Some(label)
     }
 
+    fn xfer_operation(&self, xfer: &spec::Transfer, operation_id: Option<String>) -> Operation {
+        let summary = xfer
+            .summary
+            .clone()
+            .or_else(|| xfer.desc.clone())
+            .or_else(|| operation_id.clone());
+        let description = xfer.desc.clone();
+
+        Operation {
+            summary,
+            description,
+            operation_id,
+            parameters: self.xfer_params(xfer),
+            request_body: self.xfer_request(xfer),
+            responses: self.xfer_responses(xfer),
+            tags: xfer.tags.clone(),
+            ..Default::default()
+        }
+    }
+
     fn relation_path_item(&self, rel: &spec::Relation) -> PathItem {
         let mut path_item = PathItem {
             parameters: self.uri_params(&rel.uri),
@@ -476,23 +827,7 @@ impl Builder {
 
         for (method, xfer) in xfers {
             let operation_id = self.xfer_id(xfer, method, &rel.uri);
-            let summary = xfer
-                .summary
-                .clone()
-                .or_else(|| xfer.desc.clone())
-                .or_else(|| operation_id.clone());
-            let description = xfer.desc.clone();
-
-            let op = Operation {
-                summary,
-                description,
-                operation_id,
-                parameters: self.xfer_params(xfer),
-                request_body: self.xfer_request(xfer),
-                responses: self.xfer_responses(xfer),
-                tags: xfer.tags.clone(),
-                ..Default::default()
-            };
+            let op = self.xfer_operation(xfer, operation_id);
 
             match method {
                 atom::Method::Get => path_item.get = Some(op),
@@ -509,7 +844,11 @@ impl Builder {
     }
 
     fn all_paths(&self) -> Paths {
-        let paths = if let Some(spec) = &self.spec {
+        let is_main = match &self.scope {
+            Some(scope) => scope.current == scope.main,
+            None => true,
+        };
+        let paths = if let (Some(spec), true) = (&self.spec, is_main) {
             spec.rels
                 .iter()
                 .map(|(pattern, rel)| {
@@ -528,16 +867,44 @@ impl Builder {
         }
     }
 
+    /// Generates the components for the recursive references that could not be inlined.
+    fn recursive_components(&self, spec: &spec::Spec) -> IndexMap<String, ReferenceOr<Schema>> {
+        let mut schemas = IndexMap::new();
+        let mut done = Vec::new();
+        loop {
+            let pending: Vec<_> = self
+                .recursive
+                .borrow()
+                .iter()
+                .filter(|n| !done.contains(*n))
+                .cloned()
+                .collect();
+            if pending.is_empty() {
+                break schemas;
+            }
+            for name in pending {
+                if let Some(spec::Reference::Schema(s)) = spec.refs.get(&name) {
+                    self.expanding.borrow_mut().push(name.clone());
+                    schemas.insert(name.untagged(), self.component(s));
+                    self.expanding.borrow_mut().pop();
+                }
+                done.push(name);
+            }
+        }
+    }
+
     fn all_components(&self) -> Components {
-        let schemas = if let Some(spec) = &self.spec {
-            spec.refs
+        let schemas = match &self.spec {
+            Some(spec) if self.dereference => self.recursive_components(spec),
+            Some(spec) => spec
+                .refs
                 .iter()
+                .filter(|(name, _)| self.is_local(name))
                 .flat_map(|(name, reference)| match reference {
-                    spec::Reference::Schema(s) => Some((name.untagged(), self.schema(s))),
+                    spec::Reference::Schema(s) => Some((name.untagged(), self.component(s))),
                 })
-                .collect()
-        } else {
-            Default::default()
+                .collect(),
+            None => Default::default(),
         };
         Components {
             schemas,
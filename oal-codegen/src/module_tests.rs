@@ -0,0 +1,117 @@
+use crate::errors::Kind;
+use crate::{module_paths, module_reference, Builder};
+use oal_compiler::spec::Spec;
+use oal_compiler::{Locator, ModuleSet, Program};
+use oal_syntax::parse;
+use openapiv3::ReferenceOr;
+
+#[test]
+fn module_paths_nested() -> anyhow::Result<()> {
+    let main = Locator::try_from("file:///p/api/main.oal")?;
+    let nested = Locator::try_from("file:///p/api/schemas/a.oal")?;
+
+    let paths = module_paths(&main, &[main.clone(), nested.clone()])?;
+
+    assert_eq!(paths[&main], "main.yaml");
+    assert_eq!(paths[&nested], "schemas/a.yaml");
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn module_paths_outside() -> anyhow::Result<()> {
+    let main = Locator::try_from("file:///p/api/main.oal")?;
+    let nested = Locator::try_from("file:///p/api/schemas/a.oal")?;
+    let outside = Locator::try_from("file:///p/common/x.yaml")?;
+    let remote = Locator::try_from("https://example.com/shared/y.json")?;
+
+    let modules = [
+        main.clone(),
+        nested.clone(),
+        outside.clone(),
+        remote.clone(),
+    ];
+    let paths = module_paths(&main, &modules)?;
+
+    assert_eq!(paths[&main], "api/main.yaml");
+    assert_eq!(paths[&nested], "api/schemas/a.yaml");
+    assert_eq!(paths[&outside], "common/x.yaml");
+    assert_eq!(paths[&remote], "y.yaml");
+
+    assert_eq!(
+        module_reference(&paths[&main], &paths[&nested]),
+        "schemas/a.yaml"
+    );
+    assert_eq!(
+        module_reference(&paths[&nested], &paths[&main]),
+        "../main.yaml"
+    );
+    assert_eq!(
+        module_reference(&paths[&nested], &paths[&outside]),
+        "../../common/x.yaml"
+    );
+    assert_eq!(
+        module_reference(&paths[&outside], &paths[&remote]),
+        "../y.yaml"
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn module_paths_conflict() -> anyhow::Result<()> {
+    let main = Locator::try_from("file:///p/main.oal")?;
+    let first = Locator::try_from("https://example.com/common.oal")?;
+    let second = Locator::try_from("https://example.org/common.oal")?;
+
+    let err = module_paths(&main, &[main.clone(), first, second]).expect_err("expected conflict");
+
+    assert_eq!(err.kind, Kind::Conflict);
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn module_split_same_file_names() -> anyhow::Result<()> {
+    let main = &Locator::try_from("file:///p/api/main.oal")?;
+    let loader = |m: &Locator| -> anyhow::Result<Program> {
+        let code = match m.url.path() {
+            "/p/x/lib/common.oal" => "let @a = { 'id str };",
+            "/p/y/lib/common.oal" => r#"use "../../x/lib/common.oal"; let @b = { 'a @a };"#,
+            _ => r#"use "../x/lib/common.oal"; use "../y/lib/common.oal"; res / ( get -> @b );"#,
+        };
+        Ok(parse(code)?)
+    };
+    let compiler = |mods: &ModuleSet, l: &Locator, p: Program| -> anyhow::Result<Program> {
+        Ok(oal_compiler::compile(mods, l, p)?)
+    };
+    let mods = oal_compiler::load(main, loader, compiler)?;
+    let spec = Spec::try_from(&mods)?;
+
+    let modules = Builder::new().with_spec(spec).into_modules(main)?;
+    let first = &Locator::try_from("file:///p/x/lib/common.oal")?;
+    let second = &Locator::try_from("file:///p/y/lib/common.oal")?;
+
+    assert_eq!(modules.len(), 3);
+    assert_eq!(modules[main].0, "api/main.yaml");
+    assert_eq!(modules[first].0, "x/lib/common.yaml");
+    assert_eq!(modules[second].0, "y/lib/common.yaml");
+
+    let (_, second) = &modules[second];
+    let schema = &second
+        .components
+        .as_ref()
+        .expect("expected components")
+        .schemas["b"];
+    let ReferenceOr::Item(schema) = schema else {
+        panic!("expected schema")
+    };
+    let json = serde_json::to_value(schema)?;
+
+    assert_eq!(
+        json["properties"]["a"]["$ref"],
+        "../../x/lib/common.yaml#/components/schemas/a"
+    );
+
+    anyhow::Ok(())
+}
@@ -0,0 +1,138 @@
+use crate::errors::{Error, Kind, Result};
+use openapiv3::OpenAPI;
+use serde::Deserialize;
+use serde_json::Value;
+use serde_json_path::JsonPath;
+
+/// An OpenAPI Overlay 1.0 document.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Overlay {
+    pub overlay: String,
+    pub info: OverlayInfo,
+    #[serde(default)]
+    pub extends: Option<String>,
+    pub actions: Vec<Action>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct OverlayInfo {
+    pub title: String,
+    pub version: String,
+}
+
+/// A single overlay action applying to the nodes selected by a JSONPath target.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Action {
+    pub target: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub update: Option<Value>,
+    #[serde(default)]
+    pub remove: bool,
+}
+
+/// A step in the location of a node within a JSON document.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Step {
+    Name(String),
+    Index(usize),
+}
+
+type Location = Vec<Step>;
+
+/// Merges an update value into a target node.
+///
+/// Objects are merged recursively, arrays are extended and
+/// any other value is replaced.
+fn merge(target: &mut Value, update: &Value) {
+    match (target, update) {
+        (Value::Object(t), Value::Object(u)) => {
+            for (k, v) in u.iter() {
+                match t.get_mut(k) {
+                    Some(node) => merge(node, v),
+                    None => {
+                        t.insert(k.clone(), v.clone());
+                    }
+                }
+            }
+        }
+        (Value::Array(t), Value::Array(u)) => t.extend(u.iter().cloned()),
+        (Value::Array(t), u) => t.push(u.clone()),
+        (t, u) => *t = u.clone(),
+    }
+}
+
+fn node_mut<'a>(root: &'a mut Value, loc: &[Step]) -> Option<&'a mut Value> {
+    loc.iter().try_fold(root, |node, step| match step {
+        Step::Name(n) => node.get_mut(n.as_str()),
+        Step::Index(i) => node.get_mut(*i),
+    })
+}
+
+fn remove(root: &mut Value, loc: &[Step]) {
+    if let Some((last, parent)) = loc.split_last() {
+        match (node_mut(root, parent), last) {
+            (Some(Value::Object(o)), Step::Name(n)) => {
+                o.remove(n);
+            }
+            (Some(Value::Array(a)), Step::Index(i)) if *i < a.len() => {
+                a.remove(*i);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Action {
+    fn locate(&self, doc: &Value) -> Result<Vec<Location>> {
+        let path = JsonPath::parse(self.target.as_str()).map_err(|e| {
+            Error::new(Kind::InvalidOverlay, "invalid target")
+                .with(&self.target)
+                .with(&e.to_string())
+        })?;
+        let locations: Vec<Location> = path
+            .query_located(doc)
+            .locations()
+            .map(|l| {
+                l.iter()
+                    .map(|e| match e.as_index() {
+                        Some(i) => Step::Index(i),
+                        None => Step::Name(e.as_name().unwrap_or_default().to_owned()),
+                    })
+                    .collect()
+            })
+            .collect();
+        if locations.is_empty() {
+            Err(Error::new(Kind::NoMatch, "target matches nothing").with(&self.target))
+        } else {
+            Ok(locations)
+        }
+    }
+
+    fn apply(&self, doc: &mut Value) -> Result<()> {
+        let mut locations = self.locate(doc)?;
+        if self.remove {
+            // Removing in reverse order preserves the indices of the remaining nodes.
+            locations.sort();
+            locations.iter().rev().for_each(|l| remove(doc, l));
+        } else if let Some(update) = &self.update {
+            locations.iter().for_each(|l| {
+                if let Some(node) = node_mut(doc, l) {
+                    merge(node, update)
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Overlay {
+    /// Applies all the actions of the overlay in order to the given OpenAPI definition.
+    pub fn apply(&self, api: OpenAPI) -> Result<OpenAPI> {
+        let mut doc = serde_json::to_value(api)?;
+        self.actions.iter().try_for_each(|a| a.apply(&mut doc))?;
+        let api = serde_json::from_value(doc)?;
+        Ok(api)
+    }
+}
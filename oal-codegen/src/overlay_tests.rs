@@ -0,0 +1,82 @@
+use crate::errors::Kind;
+use crate::Overlay;
+use openapiv3::OpenAPI;
+use serde_json::json;
+
+fn api() -> OpenAPI {
+    serde_json::from_value(json!({
+        "openapi": "3.0.3",
+        "info": { "title": "test", "version": "0.1.0" },
+        "paths": {
+            "/a": {
+                "get": { "responses": { "200": { "description": "ok" } } },
+                "put": { "tags": ["internal"], "responses": {} }
+            },
+            "/b": {
+                "get": { "tags": ["internal"], "responses": {} }
+            }
+        }
+    }))
+    .expect("invalid document")
+}
+
+fn overlay(actions: serde_json::Value) -> Overlay {
+    serde_json::from_value(json!({
+        "overlay": "1.0.0",
+        "info": { "title": "test", "version": "0.1.0" },
+        "actions": actions
+    }))
+    .expect("invalid overlay")
+}
+
+#[test]
+fn overlay_update() -> anyhow::Result<()> {
+    let o = overlay(json!([
+        { "target": "$.info", "update": { "description": "public API" } },
+        { "target": "$.paths['/a'].get", "update": { "tags": ["public"], "x-public": true } }
+    ]));
+
+    let api = o.apply(api())?;
+
+    assert_eq!(api.info.description, Some("public API".to_owned()));
+
+    let op = api.paths.paths["/a"]
+        .as_item()
+        .and_then(|p| p.get.as_ref())
+        .expect("expected get operation");
+
+    assert_eq!(op.tags, vec!["public".to_owned()]);
+    assert!(op.extensions.contains_key("x-public"));
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn overlay_remove() -> anyhow::Result<()> {
+    let o = overlay(json!([
+        { "target": "$.paths.*[?@.tags[0] == 'internal']", "remove": true }
+    ]));
+
+    let api = o.apply(api())?;
+
+    let a = api.paths.paths["/a"].as_item().expect("expected path item");
+    let b = api.paths.paths["/b"].as_item().expect("expected path item");
+
+    assert!(a.get.is_some());
+    assert!(a.put.is_none());
+    assert!(b.get.is_none());
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn overlay_no_match() {
+    let o = overlay(json!([
+        { "target": "$.paths['/c']", "remove": true }
+    ]));
+
+    assert_eq!(
+        o.apply(api()).expect_err("expected no match").kind,
+        Kind::NoMatch
+    );
+}
@@ -0,0 +1,507 @@
+use crate::Builder;
+use indexmap::{indexmap, IndexMap};
+use oal_compiler::spec;
+use oal_syntax::{ast, atom};
+use std::collections::HashSet;
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in",
+    "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// The names of the generated code which types must not shadow.
+const RESERVED: &[&str] = &[
+    "Client",
+    "Option",
+    "Result",
+    "String",
+    "Vec",
+    "Box",
+    "Some",
+    "None",
+    "Ok",
+    "Err",
+    "Serialize",
+    "Deserialize",
+];
+
+const DERIVES: &str = "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]";
+
+/// Splits a name into lowercase words, at non-alphanumeric characters and case changes.
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut after_lower = false;
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            after_lower = false;
+            continue;
+        }
+        if c.is_ascii_uppercase() && after_lower {
+            words.push(std::mem::take(&mut current));
+        }
+        after_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        current.push(c.to_ascii_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Converts a name into a Rust type or variant name.
+fn pascal_case(name: &str) -> String {
+    let s: String = words(name)
+        .iter()
+        .map(|w| w[..1].to_ascii_uppercase() + &w[1..])
+        .collect();
+    match s.chars().next() {
+        None => "Unnamed".to_owned(),
+        Some(c) if c.is_ascii_digit() => format!("T{}", s),
+        _ if s == "Self" => "SelfType".to_owned(),
+        _ => s,
+    }
+}
+
+/// Converts a name into a Cargo package name, which can neither be a keyword nor start with a digit.
+fn package_name(name: &str) -> String {
+    let s = words(name).join("-");
+    match s.chars().next() {
+        None => "api".to_owned(),
+        Some(c) if c.is_ascii_digit() => format!("api-{}", s),
+        _ if KEYWORDS.contains(&s.as_str()) => format!("{}-api", s),
+        _ => s,
+    }
+}
+
+/// Converts a name into a Rust field, argument or method name.
+fn snake_case(name: &str) -> String {
+    let s = words(name).join("_");
+    match s.chars().next() {
+        None => "unnamed".to_owned(),
+        Some(c) if c.is_ascii_digit() => format!("_{}", s),
+        _ if KEYWORDS.contains(&s.as_str()) => format!("{}_", s),
+        _ => s,
+    }
+}
+
+/// Returns a name not yet in use, by appending a number if needed.
+fn unique(used: &mut HashSet<String>, name: String) -> String {
+    let mut candidate = name.clone();
+    let mut n = 2;
+    while used.contains(&candidate) {
+        candidate = format!("{}{}", name, n);
+        n += 1;
+    }
+    used.insert(candidate.clone());
+    candidate
+}
+
+/// Renders a description as doc comment lines, with the given indentation.
+fn doc(desc: Option<&str>, indent: &str) -> String {
+    desc.map(|d| {
+        d.lines()
+            .map(|l| format!("{}/// {}\n", indent, l).replace("/// \n", "///\n"))
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Renders the serde attribute of a field or variant, if any.
+fn serde_attr(indent: &str, args: &[String]) -> String {
+    if args.is_empty() {
+        String::new()
+    } else {
+        format!("{}#[serde({})]\n", indent, args.join(", "))
+    }
+}
+
+/// A generator of Rust model types and client trait for a specification.
+///
+/// Objects become structs, sums and alternatives become untagged enums, joins become structs
+/// flattening their operands, string enumerations become enums, and properties not declared
+/// with `required: true` become options. Anonymous schemas get types named after their context.
+pub struct RustBuilder {
+    spec: spec::Spec,
+}
+
+struct Generator<'a> {
+    spec: &'a spec::Spec,
+    /// The type names of the references
+    refs: IndexMap<atom::Ident, String>,
+    /// The type names in use
+    names: HashSet<String>,
+    /// The generated items, in order
+    items: Vec<String>,
+}
+
+impl<'a> Generator<'a> {
+    fn new(spec: &'a spec::Spec) -> Self {
+        let mut names: HashSet<_> = RESERVED.iter().map(|n| n.to_string()).collect();
+        let refs = spec
+            .refs
+            .keys()
+            .map(|name| {
+                let ty = unique(&mut names, pascal_case(&name.untagged()));
+                (name.clone(), ty)
+            })
+            .collect();
+        Generator {
+            spec,
+            refs,
+            names,
+            items: Vec::new(),
+        }
+    }
+
+    fn fresh(&mut self, hint: &str) -> String {
+        unique(&mut self.names, pascal_case(hint))
+    }
+
+    /// Returns the type of a schema, generating the types of anonymous schemas as needed.
+    fn ty(&mut self, s: &spec::Schema, hint: &str) -> String {
+        match &s.expr {
+            spec::SchemaExpr::Num(_) => "f64".to_owned(),
+            spec::SchemaExpr::Int(_) => "i64".to_owned(),
+            spec::SchemaExpr::Bool(_) => "bool".to_owned(),
+            spec::SchemaExpr::Str(p) if p.enumeration.is_empty() => "String".to_owned(),
+            spec::SchemaExpr::Uri(_) | spec::SchemaExpr::Rel(_) => "String".to_owned(),
+            spec::SchemaExpr::Array(array) => {
+                format!("Vec<{}>", self.ty(&array.item, &format!("{} item", hint)))
+            }
+            spec::SchemaExpr::Ref(name) => self
+                .refs
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| pascal_case(&name.untagged())),
+            _ => {
+                let name = self.fresh(hint);
+                self.declare(&name, s);
+                name
+            }
+        }
+    }
+
+    /// Generates the item declaring a type for a schema.
+    fn declare(&mut self, name: &str, s: &spec::Schema) {
+        let desc = s.desc.as_deref();
+        match &s.expr {
+            spec::SchemaExpr::Object(obj) => self.structure(name, obj, desc),
+            spec::SchemaExpr::Str(p) if !p.enumeration.is_empty() => {
+                self.enumeration(name, &p.enumeration, desc)
+            }
+            spec::SchemaExpr::Op(op) if op.op == ast::Operator::Join => {
+                self.join(name, &op.schemas, desc)
+            }
+            spec::SchemaExpr::Op(op) => self.alternatives(name, &op.schemas, desc),
+            _ => {
+                let ty = self.ty(s, &format!("{} value", name));
+                let item = format!("{}pub type {} = {};\n", doc(desc, ""), name, ty);
+                self.items.push(item);
+            }
+        }
+    }
+
+    fn structure(&mut self, name: &str, obj: &spec::Object, desc: Option<&str>) {
+        let mut used = HashSet::new();
+        let mut fields = String::new();
+        for p in obj.props.iter() {
+            let prop = p.name.as_ref();
+            let field = unique(&mut used, snake_case(prop));
+            let ty = self.ty(&p.schema, &format!("{} {}", name, prop));
+            let mut args = Vec::new();
+            if field != prop {
+                args.push(format!("rename = \"{}\"", prop.escape_default()));
+            }
+            let ty = if p.schema.required == Some(true) {
+                ty
+            } else {
+                args.push("default, skip_serializing_if = \"Option::is_none\"".to_owned());
+                format!("Option<{}>", ty)
+            };
+            fields.push_str(&doc(p.schema.desc.as_deref(), "    "));
+            fields.push_str(&serde_attr("    ", &args));
+            fields.push_str(&format!("    pub {}: {},\n", field, ty));
+        }
+        self.push_struct(name, desc, &fields);
+    }
+
+    fn push_struct(&mut self, name: &str, desc: Option<&str>, fields: &str) {
+        let body = if fields.is_empty() {
+            "{}".to_owned()
+        } else {
+            format!("{{\n{}}}", fields)
+        };
+        self.items.push(format!(
+            "{}{}\npub struct {} {}\n",
+            doc(desc, ""),
+            DERIVES,
+            name,
+            body
+        ));
+    }
+
+    fn join(&mut self, name: &str, schemas: &[spec::Schema], desc: Option<&str>) {
+        let mut used = HashSet::new();
+        let mut fields = String::new();
+        for (i, s) in schemas.iter().enumerate() {
+            let field = match &s.expr {
+                spec::SchemaExpr::Ref(r) => snake_case(&r.untagged()),
+                _ => format!("part{}", i + 1),
+            };
+            let field = unique(&mut used, field);
+            let ty = self.ty(s, &format!("{} part {}", name, i + 1));
+            fields.push_str(&format!(
+                "    #[serde(flatten)]\n    pub {}: {},\n",
+                field, ty
+            ));
+        }
+        self.push_struct(name, desc, &fields);
+    }
+
+    fn alternatives(&mut self, name: &str, schemas: &[spec::Schema], desc: Option<&str>) {
+        let mut used = HashSet::new();
+        let mut variants = String::new();
+        for (i, s) in schemas.iter().enumerate() {
+            let variant = match &s.expr {
+                spec::SchemaExpr::Num(_) => "Number".to_owned(),
+                spec::SchemaExpr::Int(_) => "Integer".to_owned(),
+                spec::SchemaExpr::Bool(_) => "Boolean".to_owned(),
+                spec::SchemaExpr::Str(_) => "String".to_owned(),
+                spec::SchemaExpr::Uri(_) | spec::SchemaExpr::Rel(_) => "Uri".to_owned(),
+                spec::SchemaExpr::Array(_) => "Array".to_owned(),
+                spec::SchemaExpr::Ref(r) => pascal_case(&r.untagged()),
+                _ => format!("Variant{}", i + 1),
+            };
+            let variant = unique(&mut used, variant);
+            let ty = self.ty(s, &format!("{} {}", name, variant));
+            variants.push_str(&format!("    {}({}),\n", variant, ty));
+        }
+        self.items.push(format!(
+            "{}{}\n#[serde(untagged)]\npub enum {} {{\n{}}}\n",
+            doc(desc, ""),
+            DERIVES,
+            name,
+            variants
+        ));
+    }
+
+    fn enumeration(&mut self, name: &str, values: &[String], desc: Option<&str>) {
+        let mut used = HashSet::new();
+        let mut variants = String::new();
+        for value in values.iter() {
+            let variant = unique(&mut used, pascal_case(value));
+            variants.push_str(&format!(
+                "    #[serde(rename = \"{}\")]\n    {},\n",
+                value.escape_default(),
+                variant
+            ));
+        }
+        self.items.push(format!(
+            "{}#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]\n\
+             pub enum {} {{\n{}}}\n",
+            doc(desc, ""),
+            name,
+            variants
+        ));
+    }
+
+    /// Returns the type of a parameter or body argument, borrowed.
+    fn arg(&mut self, s: &spec::Schema, hint: &str) -> String {
+        match self.ty(s, hint) {
+            ty if ty == "String" => "&str".to_owned(),
+            ty if ty == "i64" || ty == "f64" || ty == "bool" => ty,
+            ty => format!("&{}", ty),
+        }
+    }
+
+    /// Generates the query parameters of a transfer as a struct, if any.
+    fn query(
+        &mut self,
+        prefix: &str,
+        rel: &spec::Relation,
+        xfer: &spec::Transfer,
+    ) -> Option<String> {
+        let props: Vec<_> = rel
+            .uri
+            .params
+            .iter()
+            .chain(xfer.params.iter())
+            .flat_map(|o| o.props.iter())
+            .map(|p| {
+                // Parameters are required by their statement annotation, not their schema.
+                let mut schema = p.schema.clone();
+                schema.required = p.required;
+                schema.desc = p.desc.clone().or(schema.desc);
+                spec::Property {
+                    schema,
+                    ..p.clone()
+                }
+            })
+            .collect();
+        if props.is_empty() {
+            return None;
+        }
+        let name = self.fresh(&format!("{} query", prefix));
+        self.structure(&name, &spec::Object { props }, None);
+        Some(name)
+    }
+
+    /// Generates the enumeration of the responses of a transfer, by status.
+    fn responses(&mut self, prefix: &str, xfer: &spec::Transfer) -> String {
+        let name = self.fresh(&format!("{} response", prefix));
+        let mut used = HashSet::new();
+        let mut variants = String::new();
+        for content in xfer.ranges.values() {
+            let status = match content.status {
+                Some(atom::HttpStatus::Code(code)) => format!("Status{}", code),
+                Some(atom::HttpStatus::Range(range)) => {
+                    let class = match range {
+                        atom::HttpStatusRange::Info => 1,
+                        atom::HttpStatusRange::Success => 2,
+                        atom::HttpStatusRange::Redirect => 3,
+                        atom::HttpStatusRange::ClientError => 4,
+                        atom::HttpStatusRange::ServerError => 5,
+                    };
+                    format!("Status{}XX", class)
+                }
+                None => "Default".to_owned(),
+            };
+            let variant = unique(&mut used, status);
+            variants.push_str(&doc(content.desc.as_deref(), "    "));
+            match &content.schema {
+                Some(schema) => {
+                    let ty = self.ty(schema, &format!("{} {}", name, variant));
+                    variants.push_str(&format!("    {}({}),\n", variant, ty));
+                }
+                None => variants.push_str(&format!("    {},\n", variant)),
+            }
+        }
+        self.items.push(format!(
+            "/// The responses of `{}`, by status.\n\
+             #[derive(Debug, Clone, PartialEq)]\npub enum {} {{\n{}}}\n",
+            snake_case(prefix),
+            name,
+            variants
+        ));
+        name
+    }
+
+    fn method(
+        &mut self,
+        used: &mut HashSet<String>,
+        pattern: &str,
+        rel: &spec::Relation,
+        method: atom::Method,
+        xfer: &spec::Transfer,
+    ) -> String {
+        let builder = Builder::new();
+        let id = builder
+            .xfer_id(xfer, method, &rel.uri)
+            .unwrap_or_else(|| pattern.to_owned());
+        let fn_name = unique(used, snake_case(&id));
+        let prefix = pascal_case(&fn_name);
+
+        let mut args = vec!["&self".to_owned()];
+        let mut arg_names = HashSet::new();
+        for seg in rel.uri.path.iter() {
+            if let spec::UriSegment::Variable(p) = seg {
+                let arg = unique(&mut arg_names, snake_case(p.name.as_ref()));
+                let ty = self.arg(&p.schema, &format!("{} {}", prefix, p.name.as_ref()));
+                args.push(format!("{}: {}", arg, ty));
+            }
+        }
+        if let Some(query) = self.query(&prefix, rel, xfer) {
+            let arg = unique(&mut arg_names, "query".to_owned());
+            args.push(format!("{}: &{}", arg, query));
+        }
+        if let Some(schema) = &xfer.domain.schema {
+            let arg = unique(&mut arg_names, "body".to_owned());
+            let ty = self.arg(schema, &format!("{} body", prefix));
+            args.push(format!("{}: {}", arg, ty));
+        }
+        let response = self.responses(&prefix, xfer);
+
+        let label = builder.method_label(method).to_uppercase();
+        let mut text = doc(xfer.summary.as_deref().or(xfer.desc.as_deref()), "    ");
+        if !text.is_empty() {
+            text.push_str("    ///\n");
+        }
+        text.push_str(&format!("    /// `{} {}`\n", label, pattern));
+        text.push_str(&format!(
+            "    fn {}({}) -> Result<{}, Self::Error>;\n",
+            fn_name,
+            args.join(", "),
+            response
+        ));
+        text
+    }
+
+    fn client(&mut self) -> String {
+        let mut used = HashSet::new();
+        let mut methods = Vec::new();
+        for (pattern, rel) in self.spec.rels.iter() {
+            for (method, xfer) in rel.xfers.iter() {
+                if let Some(xfer) = xfer {
+                    methods.push(self.method(&mut used, pattern, rel, method, xfer));
+                }
+            }
+        }
+        format!(
+            "/// A client of the API, each method sending a request and decoding its response.\n\
+             pub trait Client {{\n    \
+             /// The error of a request which did not get a documented response\n    \
+             type Error;\n\n{}}}\n",
+            methods.join("\n")
+        )
+    }
+
+    fn module(mut self) -> String {
+        let spec = self.spec;
+        for (name, reference) in spec.refs.iter() {
+            let spec::Reference::Schema(s) = reference;
+            let ty = self.refs[name].clone();
+            self.declare(&ty, s);
+        }
+        let client = self.client();
+        let mut out = String::from(
+            "//! The model types and client of an API, generated from a program.\n\n\
+             use serde::{Deserialize, Serialize};\n",
+        );
+        for item in self.items.iter().chain(std::iter::once(&client)) {
+            out.push('\n');
+            out.push_str(item);
+        }
+        out
+    }
+}
+
+impl RustBuilder {
+    pub fn new(spec: spec::Spec) -> Self {
+        RustBuilder { spec }
+    }
+
+    /// Generates the source of a Rust module with the model types and the client trait.
+    pub fn into_source(self) -> String {
+        Generator::new(&self.spec).module()
+    }
+
+    /// Generates a library crate with the given package name, indexed by relative file path.
+    pub fn into_crate(self, name: &str) -> IndexMap<String, String> {
+        let manifest = format!(
+            "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+             [dependencies]\nserde = {{ version = \"1\", features = [\"derive\"] }}\n",
+            package_name(name)
+        );
+        indexmap! {
+            "Cargo.toml".to_owned() => manifest,
+            "src/lib.rs".to_owned() => self.into_source(),
+        }
+    }
+}
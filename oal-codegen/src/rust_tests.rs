@@ -0,0 +1,229 @@
+use crate::tests::eval;
+use crate::RustBuilder;
+use indexmap::IndexMap;
+use oal_compiler::spec::{Object, PrimString, Property, Reference, Schema, SchemaExpr, Spec};
+use oal_syntax::atom::Ident;
+use std::process::Command;
+
+const MODELS: &str = r#"
+    let @base = { 'id int `required: true` };
+    let @order = @base & {
+      'totalAmount num `required: true, description: "the total"`
+    , 'status str `enum: [open, "in-progress"]`
+    , 'lines [{ 'sku str `required: true` }]
+    , 'type str
+    };
+    let @key = int | str;
+    let @shape = @base ~ { 'name str };
+    res / ( get -> @order, put -> @key, post -> @shape );
+"#;
+
+const CLIENT: &str = r#"
+    let @item = { 'id int `required: true` };
+    let @error = { 'message str };
+    # required: true
+    let limit = 'limit int;
+    # operationId: "replaceItem"
+    let replace = put { limit } : @item -> <status=204,>;
+    res /items/{ 'id int }?{ 'verbose bool } (
+      get -> <status=200, @item> :: <status=404, @error> :: <status=5XX, @error>,
+      replace
+    );
+"#;
+
+const RESERVED: &str = r#"
+    let @option = { 'value str };
+    let @result = { 'ok @option `required: true` };
+    let @string = str `enum: [self, Self]`;
+    let @vec = [@string];
+    let @box = { 'items @vec };
+    let @serialize = { 'self @box, 'type str };
+    let @self = @serialize & { 'client str };
+    let @client = { 'err int };
+    res /a ( get -> @result, put : @self -> @client );
+"#;
+
+/// Returns a specification with property names to escape, which programs cannot declare.
+fn escaped() -> anyhow::Result<Spec> {
+    let prop = |name: &str| Property {
+        name: name.into(),
+        schema: Schema {
+            expr: SchemaExpr::Str(PrimString {
+                pattern: None,
+                enumeration: vec![],
+                example: None,
+            }),
+            desc: None,
+            title: None,
+            required: Some(true),
+        },
+        desc: None,
+        required: None,
+    };
+    let mut spec = eval("let @a = {}; res / ( get -> @a );")?;
+    let schema = Schema {
+        expr: SchemaExpr::Object(Object {
+            props: vec![prop("say \"hi\""), prop("back\\slash")],
+        }),
+        desc: None,
+        title: None,
+        required: None,
+    };
+    spec.refs
+        .insert(Ident::from("@a"), Reference::Schema(schema));
+    anyhow::Ok(spec)
+}
+
+/// Type-checks a generated crate with Cargo, against serde from the local registry.
+fn check(files: &IndexMap<String, String>) -> anyhow::Result<()> {
+    let name = files["Cargo.toml"]
+        .lines()
+        .find_map(|l| l.strip_prefix("name = "))
+        .unwrap_or_default()
+        .trim_matches('"')
+        .to_owned();
+    let tmp = std::env::temp_dir().join(format!("oal-rust-{}", std::process::id()));
+    let dir = tmp.join(&name);
+    for (path, content) in files.iter() {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(path, content)?;
+    }
+    let output = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned()))
+        .args(["check", "--quiet", "--offline"])
+        .env("CARGO_TARGET_DIR", tmp.join("target"))
+        .env("RUSTFLAGS", "-D warnings")
+        .current_dir(&dir)
+        .output()?;
+    std::fs::remove_dir_all(&dir)?;
+    assert!(
+        output.status.success(),
+        "{}: {}",
+        name,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    anyhow::Ok(())
+}
+
+#[test]
+fn rust_models() -> anyhow::Result<()> {
+    let source = RustBuilder::new(eval(MODELS)?).into_source();
+
+    assert!(source.contains(
+        "pub struct Base {\n    \
+         pub id: i64,\n\
+         }"
+    ));
+    assert!(source.contains(
+        "pub struct Order {\n    \
+         #[serde(flatten)]\n    pub base: Base,\n    \
+         #[serde(flatten)]\n    pub part2: OrderPart2,\n\
+         }"
+    ));
+    assert!(source.contains(
+        "    /// the total\n    \
+         #[serde(rename = \"totalAmount\")]\n    \
+         pub total_amount: f64,\n"
+    ));
+    assert!(source.contains(
+        "    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    \
+         pub status: Option<OrderPart2Status>,\n"
+    ));
+    assert!(source.contains("    #[serde(rename = \"in-progress\")]\n    InProgress,\n"));
+    assert!(source.contains("pub lines: Option<Vec<OrderPart2LinesItem>>,"));
+    assert!(source.contains("    #[serde(rename = \"type\", default, skip_serializing_if = \"Option::is_none\")]\n    pub type_: Option<String>,\n"));
+    assert!(source.contains(
+        "#[serde(untagged)]\npub enum Key {\n    \
+         Integer(i64),\n    String(String),\n\
+         }"
+    ));
+
+    assert!(source.contains(
+        "#[serde(untagged)]\npub enum Shape {\n    \
+         Base(Base),\n    Variant2(ShapeVariant2),\n\
+         }"
+    ));
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn rust_client() -> anyhow::Result<()> {
+    let source = RustBuilder::new(eval(CLIENT)?).into_source();
+
+    assert!(source.contains(
+        "pub enum GetItemsIdResponse {\n    \
+         Status200(Item),\n    Status404(Error),\n    Status5XX(Error),\n\
+         }"
+    ));
+    assert!(source.contains(
+        "    /// `GET /items/{id}`\n    \
+         fn get_items_id(&self, id: i64, query: &GetItemsIdQuery) \
+         -> Result<GetItemsIdResponse, Self::Error>;\n"
+    ));
+    assert!(source.contains(
+        "    fn replace_item(&self, id: i64, query: &ReplaceItemQuery, body: &Item) \
+         -> Result<ReplaceItemResponse, Self::Error>;\n"
+    ));
+    assert!(source.contains("pub enum ReplaceItemResponse {\n    Status204,\n}"));
+    assert!(source.contains("    pub limit: i64,\n"));
+    assert!(source.contains("    pub verbose: Option<bool>,\n"));
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn rust_crate() -> anyhow::Result<()> {
+    let files = RustBuilder::new(eval("res / ( get -> {} );")?).into_crate("My API");
+
+    assert_eq!(
+        files.keys().collect::<Vec<_>>(),
+        vec!["Cargo.toml", "src/lib.rs"]
+    );
+    assert!(files["Cargo.toml"].contains("name = \"my-api\""));
+
+    let spec = eval("res / ( get -> {} );")?;
+    let files = RustBuilder::new(spec.clone()).into_crate("2024-api");
+    assert!(files["Cargo.toml"].contains("name = \"api-2024-api\""));
+    let files = RustBuilder::new(spec).into_crate("self");
+    assert!(files["Cargo.toml"].contains("name = \"self-api\""));
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn rust_reserved_names() -> anyhow::Result<()> {
+    let source = RustBuilder::new(eval(RESERVED)?).into_source();
+
+    assert!(source.contains("pub struct Option2 {"));
+    assert!(source.contains("pub struct SelfType {"));
+    assert!(source.contains("    pub value: Option<String>,\n"));
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn rust_escaped_renames() -> anyhow::Result<()> {
+    let source = RustBuilder::new(escaped()?).into_source();
+
+    assert!(source.contains("#[serde(rename = \"say \\\"hi\\\"\")]\n    pub say_hi: String,"));
+    assert!(source.contains("#[serde(rename = \"back\\\\slash\")]\n    pub back_slash: String,"));
+
+    anyhow::Ok(())
+}
+
+/// Builds the generated crates, which needs serde in the local Cargo registry.
+#[test]
+#[ignore]
+fn rust_crates_build() -> anyhow::Result<()> {
+    for (name, code) in [
+        ("models", MODELS),
+        ("client", CLIENT),
+        ("reserved", RESERVED),
+    ] {
+        check(&RustBuilder::new(eval(code)?).into_crate(name))?;
+    }
+    check(&RustBuilder::new(escaped()?).into_crate("escaped"))?;
+
+    anyhow::Ok(())
+}
@@ -0,0 +1,159 @@
+use crate::errors::Result;
+use crate::Builder;
+use indexmap::IndexMap;
+use oal_compiler::spec::Source;
+use openapiv3::{OpenAPI, StatusCode};
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+
+/// The extension holding the source of a node of an OpenAPI definition.
+pub const SOURCE_EXTENSION: &str = "x-oal-source";
+
+/// Escapes a key as a JSON pointer token.
+pub fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// The expressions the nodes of an OpenAPI definition are generated from, by JSON pointer.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SourceMap(IndexMap<String, Source>);
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the source of a node, unless already known.
+    pub fn insert(&mut self, pointer: String, source: Source) {
+        self.0.entry(pointer).or_insert(source);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Source)> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the source of a node, or else of its closest ancestor with a known source.
+    pub fn get(&self, pointer: &str) -> Option<&Source> {
+        let mut ptr = pointer;
+        loop {
+            if let Some(source) = self.0.get(ptr) {
+                return Some(source);
+            }
+            match ptr.rfind('/') {
+                Some(idx) => ptr = &ptr[..idx],
+                None => return None,
+            }
+        }
+    }
+
+    /// Keeps the sources of the nodes found in an OpenAPI definition only.
+    pub fn retain_nodes(&mut self, api: &OpenAPI) -> Result<()> {
+        let doc = serde_json::to_value(api)?;
+        self.0.retain(|ptr, _| doc.pointer(ptr).is_some());
+        Ok(())
+    }
+
+    /// Adds the source of each node of an OpenAPI definition as an `x-oal-source` extension.
+    ///
+    /// References cannot have extensions and are left as is.
+    pub fn annotate(&self, api: OpenAPI) -> Result<OpenAPI> {
+        let mut doc = serde_json::to_value(api)?;
+        for (ptr, source) in self.0.iter() {
+            if let Some(Value::Object(node)) = doc.pointer_mut(ptr) {
+                if !node.contains_key("$ref") {
+                    node.insert(SOURCE_EXTENSION.to_owned(), source.to_string().into());
+                }
+            }
+        }
+        Ok(serde_json::from_value(doc)?)
+    }
+}
+
+#[derive(Serialize)]
+struct SpanEntry {
+    start: (usize, usize),
+    end: (usize, usize),
+}
+
+#[derive(Serialize)]
+struct SourceEntry<'a> {
+    module: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    span: Option<SpanEntry>,
+}
+
+impl Serialize for SourceMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (ptr, source) in self.0.iter() {
+            let entry = SourceEntry {
+                module: source.loc.url.as_str(),
+                span: source.span.map(|s| SpanEntry {
+                    start: s.start,
+                    end: s.end,
+                }),
+            };
+            map.serialize_entry(ptr, &entry)?;
+        }
+        map.end()
+    }
+}
+
+impl Builder {
+    /// Maps the nodes generated from the specification to the expressions they come from.
+    ///
+    /// Path items come from relations, operations from transfers, request bodies and
+    /// responses from contents, and schema components from the module declaring them.
+    pub fn source_map(&self) -> SourceMap {
+        let mut map = SourceMap::new();
+        let spec = match &self.spec {
+            Some(spec) => spec,
+            None => return map,
+        };
+        for (pattern, rel) in spec.rels.iter() {
+            let path_ptr = format!("/paths/{}", escape_pointer(pattern));
+            if let Some(source) = &rel.source {
+                map.insert(path_ptr.clone(), source.clone());
+            }
+            let xfers = rel
+                .xfers
+                .iter()
+                .filter_map(|(m, x)| x.as_ref().map(|x| (m, x)));
+            for (method, xfer) in xfers {
+                let ptr = format!("{}/{}", path_ptr, self.method_label(method));
+                if let Some(source) = &xfer.source {
+                    map.insert(ptr.clone(), source.clone());
+                }
+                if let Some(source) = &xfer.domain.source {
+                    map.insert(format!("{}/requestBody", ptr), source.clone());
+                }
+                for ((status, _), content) in xfer.ranges.iter() {
+                    let status = match status {
+                        Some(s) => match self.http_status_code(s) {
+                            StatusCode::Code(code) => code.to_string(),
+                            StatusCode::Range(range) => format!("{}XX", range),
+                        },
+                        None => "default".to_owned(),
+                    };
+                    if let Some(source) = &content.source {
+                        map.insert(format!("{}/responses/{}", ptr, status), source.clone());
+                    }
+                }
+            }
+        }
+        for (name, source) in spec.origins.iter() {
+            let ptr = format!("/components/schemas/{}", escape_pointer(&name.untagged()));
+            map.insert(ptr, source.clone());
+        }
+        map
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{tests, Builder, SourceMap, SOURCE_EXTENSION};
+use oal_compiler::Locator;
+use openapiv3::OpenAPI;
+use serde_json::json;
+
+fn eval(code: &str) -> anyhow::Result<(OpenAPI, SourceMap)> {
+    let spec = tests::eval(code)?;
+    let builder = Builder::new().with_spec(spec);
+    let sources = builder.source_map();
+    anyhow::Ok((builder.into_openapi(), sources))
+}
+
+#[test]
+fn sourcemap_pointers() -> anyhow::Result<()> {
+    let code = r#"
+        let @a = { 'id str };
+        let ok = <@a> `description: "ok"`;
+        res /a/{ 'id str } (
+          get -> ok :: <status=404, {}>,
+          put : <@a> -> ok
+        );
+    "#;
+
+    let (_, sources) = eval(code)?;
+
+    let start = |ptr: &str| sources.get(ptr).and_then(|s| s.span).map(|s| s.start);
+    assert_eq!(start("/paths/~1a~1{id}"), Some((4, 13)));
+    assert_eq!(start("/paths/~1a~1{id}/get"), Some((5, 11)));
+    assert_eq!(
+        start("/paths/~1a~1{id}/get/responses/default"),
+        Some((3, 18))
+    );
+    assert_eq!(start("/paths/~1a~1{id}/get/responses/404"), Some((5, 24)));
+    assert_eq!(start("/paths/~1a~1{id}/put/requestBody"), Some((6, 17)));
+    assert_eq!(
+        start("/paths/~1a~1{id}/put/responses/default/content"),
+        Some((3, 18))
+    );
+    assert_eq!(start("/paths/~1a~1{id}/parameters/0"), Some((4, 13)));
+
+    let schema = sources
+        .get("/components/schemas/a/properties/id")
+        .expect("expected source");
+    assert_eq!(schema.loc, Locator::try_from("test:main")?);
+    assert_eq!(schema.span.map(|s| s.start), Some((2, 18)));
+
+    assert_eq!(sources.get("/info"), None);
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn sourcemap_annotate() -> anyhow::Result<()> {
+    let code = r#"
+        res /a ( get -> <status=204, {}> `description: "none"` );
+    "#;
+
+    let (api, sources) = eval(code)?;
+    let api = sources.annotate(api)?;
+    let doc = serde_json::to_value(api)?;
+
+    assert_eq!(
+        doc.pointer(&format!("/paths/~1a/{}", SOURCE_EXTENSION)),
+        Some(&json!("test:main 2,13..2,65"))
+    );
+    assert_eq!(
+        doc.pointer(&format!("/paths/~1a/get/{}", SOURCE_EXTENSION)),
+        Some(&json!("test:main 2,18..2,64"))
+    );
+    assert_eq!(
+        doc.pointer(&format!(
+            "/paths/~1a/get/responses/204/{}",
+            SOURCE_EXTENSION
+        )),
+        Some(&json!("test:main 2,25..2,64"))
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn sourcemap_serialize() -> anyhow::Result<()> {
+    let code = r#"
+        let @a = {};
+        res /a ( get -> @a );
+    "#;
+
+    let (api, mut sources) = eval(code)?;
+    sources.retain_nodes(&api)?;
+
+    assert_eq!(
+        serde_json::to_value(&sources)?,
+        json!({
+            "/paths/~1a": { "module": "test:main", "span": { "start": [3, 13], "end": [3, 29] } },
+            "/paths/~1a/get": { "module": "test:main", "span": { "start": [3, 18], "end": [3, 28] } },
+            "/paths/~1a/get/responses/default": {
+                "module": "test:main",
+                "span": { "start": [3, 25], "end": [3, 28] }
+            },
+            "/components/schemas/a": {
+                "module": "test:main",
+                "span": { "start": [2, 18], "end": [2, 20] }
+            },
+        })
+    );
+
+    anyhow::Ok(())
+}
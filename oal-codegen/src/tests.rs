@@ -0,0 +1,21 @@
+//! Fixtures shared by the tests of the crate.
+
+use oal_compiler::spec::Spec;
+use oal_compiler::{Locator, ModuleSet, Program};
+use oal_syntax::parse;
+
+/// Compiles a program as the main module of a set.
+pub fn compile(code: &str) -> anyhow::Result<ModuleSet> {
+    let loc = Locator::try_from("test:main")?;
+    let mut mods = ModuleSet::new(loc.clone());
+    let prg: Program = parse(code)?;
+    let prg = oal_compiler::compile(&mods, &loc, prg)?;
+    mods.insert(loc, prg);
+    anyhow::Ok(mods)
+}
+
+/// Compiles a program and evaluates its specification.
+pub fn eval(code: &str) -> anyhow::Result<Spec> {
+    let mods = compile(code)?;
+    anyhow::Ok(Spec::try_from(&mods)?)
+}
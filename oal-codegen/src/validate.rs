@@ -0,0 +1,210 @@
+use crate::sourcemap::{escape_pointer, SourceMap};
+use oal_compiler::spec::Source;
+use openapiv3::OpenAPI;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// A structural error in an OpenAPI description.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Issue {
+    /// The JSON pointer to the offending node
+    pub pointer: String,
+    pub message: String,
+    /// The expression the offending node was generated from, if any
+    pub source: Option<Source>,
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.message)?;
+        match &self.source {
+            Some(source) => write!(f, "\n  --> {}", source),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks an OpenAPI description for structural errors.
+///
+/// Local references must resolve, variables of path templates must match path parameters
+/// and conversely, responses must have a description and operation identifiers must be unique.
+/// Each issue is reported along with the expression it comes from, if known from the source map.
+pub fn validate(api: &OpenAPI, sources: &SourceMap) -> Vec<Issue> {
+    let doc = match serde_json::to_value(api) {
+        Ok(doc) => doc,
+        Err(err) => {
+            return vec![Issue {
+                pointer: "".to_owned(),
+                message: err.to_string(),
+                source: None,
+            }]
+        }
+    };
+    let mut v = Validation {
+        doc: &doc,
+        sources,
+        issues: Vec::new(),
+        operation_ids: HashMap::new(),
+    };
+    v.references("", &doc);
+    if let Some(paths) = doc.get("paths").and_then(Value::as_object) {
+        for (pattern, item) in paths.iter().filter(|(p, _)| p.starts_with('/')) {
+            let ptr = format!("/paths/{}", escape_pointer(pattern));
+            v.path_item(&ptr, pattern, item);
+        }
+    }
+    if let Some(responses) = doc
+        .pointer("/components/responses")
+        .and_then(Value::as_object)
+    {
+        for (name, response) in responses.iter() {
+            let ptr = format!("/components/responses/{}", escape_pointer(name));
+            v.response(&ptr, response);
+        }
+    }
+    v.issues
+}
+
+struct Validation<'a> {
+    doc: &'a Value,
+    sources: &'a SourceMap,
+    issues: Vec<Issue>,
+    /// The pointer to the first operation using each identifier
+    operation_ids: HashMap<&'a str, String>,
+}
+
+impl<'a> Validation<'a> {
+    fn report(&mut self, pointer: String, message: String) {
+        let source = self.sources.get(&pointer).cloned();
+        self.issues.push(Issue {
+            pointer,
+            message,
+            source,
+        })
+    }
+
+    /// Resolves a local reference, leaving external references unresolved.
+    fn resolve(&self, value: &'a Value) -> Option<&'a Value> {
+        match value.get("$ref").and_then(Value::as_str) {
+            Some(r) => r.strip_prefix('#').and_then(|p| self.doc.pointer(p)),
+            None => Some(value),
+        }
+    }
+
+    fn references(&mut self, ptr: &str, value: &'a Value) {
+        match value {
+            Value::Object(obj) => {
+                if let Some(Value::String(r)) = obj.get("$ref") {
+                    if let Some(p) = r.strip_prefix('#') {
+                        if self.doc.pointer(p).is_none() {
+                            self.report(ptr.to_owned(), format!("dangling reference {}", r));
+                        }
+                    }
+                }
+                for (key, child) in obj.iter() {
+                    self.references(&format!("{}/{}", ptr, escape_pointer(key)), child);
+                }
+            }
+            Value::Array(items) => {
+                for (idx, child) in items.iter().enumerate() {
+                    self.references(&format!("{}/{}", ptr, idx), child);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the names of the path parameters of a path item or operation.
+    fn path_params(&self, node: &'a Value) -> Vec<&'a str> {
+        node.get("parameters")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|p| self.resolve(p))
+            .filter(|p| p.get("in").and_then(Value::as_str) == Some("path"))
+            .filter_map(|p| p.get("name").and_then(Value::as_str))
+            .collect()
+    }
+
+    fn path_item(&mut self, ptr: &str, pattern: &str, item: &'a Value) {
+        let item = match self.resolve(item) {
+            Some(item) => item,
+            None => return,
+        };
+        let vars = template_vars(pattern);
+        let shared = self.path_params(item);
+        let ops: Vec<_> = METHODS
+            .iter()
+            .filter_map(|m| item.get(*m).map(|op| (*m, op)))
+            .collect();
+        if ops.is_empty() {
+            self.path_template(ptr, &vars, &shared);
+        }
+        for (method, op) in ops {
+            let ptr = format!("{}/{}", ptr, method);
+            let mut params = shared.clone();
+            params.extend(self.path_params(op));
+            self.path_template(&ptr, &vars, &params);
+            self.operation(&ptr, op);
+        }
+    }
+
+    fn path_template(&mut self, ptr: &str, vars: &[&str], params: &[&str]) {
+        for var in vars.iter().filter(|v| !params.contains(*v)) {
+            let msg = format!("path variable {} without a path parameter", var);
+            self.report(ptr.to_owned(), msg);
+        }
+        for param in params.iter().filter(|p| !vars.contains(*p)) {
+            let msg = format!("path parameter {} not in the path template", param);
+            self.report(ptr.to_owned(), msg);
+        }
+    }
+
+    fn operation(&mut self, ptr: &str, op: &'a Value) {
+        if let Some(id) = op.get("operationId").and_then(Value::as_str) {
+            match self.operation_ids.get(id) {
+                Some(first) => {
+                    let msg = format!("duplicate operation identifier {}, used by {}", id, first);
+                    self.report(ptr.to_owned(), msg);
+                }
+                None => {
+                    self.operation_ids.insert(id, ptr.to_owned());
+                }
+            }
+        }
+        let responses = op
+            .get("responses")
+            .and_then(Value::as_object)
+            .map(Map::iter)
+            .into_iter()
+            .flatten();
+        for (status, response) in responses {
+            let ptr = format!("{}/responses/{}", ptr, escape_pointer(status));
+            self.response(&ptr, response);
+        }
+    }
+
+    fn response(&mut self, ptr: &str, response: &'a Value) {
+        if response.get("$ref").is_some() {
+            return;
+        }
+        let desc = response.get("description").and_then(Value::as_str);
+        if desc.filter(|d| !d.is_empty()).is_none() {
+            self.report(ptr.to_owned(), "empty response description".to_owned());
+        }
+    }
+}
+
+/// Returns the variables of a path template.
+fn template_vars(pattern: &str) -> Vec<&str> {
+    pattern
+        .split('{')
+        .skip(1)
+        .filter_map(|s| s.split_once('}').map(|(var, _)| var))
+        .collect()
+}
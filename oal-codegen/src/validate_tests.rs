@@ -0,0 +1,124 @@
+use crate::{tests, validate, Builder, SourceMap};
+use oal_compiler::Locator;
+use openapiv3::{OpenAPI, ReferenceOr};
+
+fn eval(code: &str) -> anyhow::Result<(OpenAPI, SourceMap)> {
+    let spec = tests::eval(code)?;
+    let builder = Builder::new().with_spec(spec);
+    let sources = builder.source_map();
+    anyhow::Ok((builder.into_openapi(), sources))
+}
+
+#[test]
+fn validate_valid() -> anyhow::Result<()> {
+    let code = r#"
+        let @a = { 'id str };
+        let ok = <@a> `description: "ok"`;
+        res /a/{ 'id str } ( get -> ok, put : <@a> -> ok );
+    "#;
+
+    let (api, sources) = eval(code)?;
+
+    assert_eq!(validate(&api, &sources), vec![]);
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn validate_minimal() -> anyhow::Result<()> {
+    let (api, sources) = eval("res / ( get -> {} );")?;
+
+    assert_eq!(validate(&api, &sources), vec![]);
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn validate_response_description() -> anyhow::Result<()> {
+    let code = r#"
+        res /a ( get -> <status=200, {}> );
+    "#;
+
+    // The builder always describes responses, but a base or an overlay may not.
+    let (mut api, sources) = eval(code)?;
+    let op = match api.paths.paths.get_mut("/a") {
+        Some(ReferenceOr::Item(path)) => path.get.as_mut().expect("expected get operation"),
+        _ => panic!("expected path item"),
+    };
+    for response in op.responses.responses.values_mut() {
+        if let ReferenceOr::Item(response) = response {
+            response.description.clear();
+        }
+    }
+    let issues = validate(&api, &sources);
+
+    assert_eq!(issues.len(), 1);
+    let issue = &issues[0];
+    assert_eq!(issue.pointer, "/paths/~1a/get/responses/200");
+    assert_eq!(issue.message, "empty response description");
+    let source = issue.source.as_ref().expect("expected source");
+    assert_eq!(source.loc, Locator::try_from("test:main")?);
+    // Issues are located from the source map, so at the content of the response itself.
+    assert_eq!(source.span.expect("expected span").start, (2, 25));
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn validate_operation_ids() -> anyhow::Result<()> {
+    let code = r#"
+        # operationId: "same"
+        let op = get -> <> `description: "ok"`;
+        res /a ( op );
+        res /b ( op );
+    "#;
+
+    let (api, sources) = eval(code)?;
+    let issues = validate(&api, &sources);
+
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].pointer, "/paths/~1b/get");
+    assert_eq!(
+        issues[0].message,
+        "duplicate operation identifier same, used by /paths/~1a/get"
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn validate_structure() -> anyhow::Result<()> {
+    let code = r#"
+        let @a = {};
+        res /a/{ 'id str } ( get -> <@a> `description: "ok"` );
+    "#;
+
+    let (mut api, sources) = eval(code)?;
+    api.components.as_mut().unwrap().schemas.clear();
+    match api.paths.paths.get_mut("/a/{id}") {
+        Some(ReferenceOr::Item(item)) => item.parameters.clear(),
+        _ => panic!("expected path item"),
+    }
+    let issues = validate(&api, &sources);
+
+    let found: Vec<_> = issues
+        .iter()
+        .map(|i| (i.pointer.as_str(), i.message.as_str()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (
+                "/paths/~1a~1{id}/get/responses/default/content/application~1json/schema",
+                "dangling reference #/components/schemas/a"
+            ),
+            (
+                "/paths/~1a~1{id}/get",
+                "path variable id without a path parameter"
+            ),
+        ]
+    );
+    assert!(issues.iter().all(|i| i.source.is_some()));
+
+    anyhow::Ok(())
+}
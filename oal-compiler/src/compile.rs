@@ -1,7 +1,7 @@
 use crate::annotation::{annotate, Annotated};
 use crate::errors::Result;
 use crate::inference::{constrain, substitute, tag_type, InferenceSet, TagSeq};
-use crate::locator::Locator;
+use crate::locator::{locate, Located, Locator};
 use crate::module::ModuleSet;
 use crate::reduction::{reduce, Semigroup};
 use crate::scan::Scan;
@@ -18,9 +18,11 @@ pub fn compile<T>(
     mut prg: ast::Program<T>,
 ) -> Result<ast::Program<T>>
 where
-    T: AsExpr + Tagged + Annotated + Semigroup,
+    T: AsExpr + Tagged + Annotated + Semigroup + Located,
 {
-    let new_env = || Env::new(Some(mods));
+    let new_env = || Env::new(Some(mods)).within_module(loc);
+
+    prg.transform(&mut loc.clone(), &mut new_env(), &mut locate)?;
 
     prg.transform(&mut TagSeq::new(loc.clone()), &mut new_env(), &mut tag_type)?;
 
@@ -0,0 +1,582 @@
+//! A comparison of two versions of a specification, classifying changes as breaking or not.
+
+use crate::spec::{
+    Content, Object, Property, Ranges, Reference, Relation, Schema, SchemaExpr, Spec, Transfer,
+    UriSegment,
+};
+use oal_syntax::ast::Operator;
+use oal_syntax::atom::{HttpStatus, HttpStatusRange, Ident, Method};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+/// Whether a change can break existing clients.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+    Breaking,
+    NonBreaking,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Breaking => write!(f, "breaking"),
+            Severity::NonBreaking => write!(f, "non-breaking"),
+        }
+    }
+}
+
+/// A difference between two versions of a specification.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Change {
+    pub severity: Severity,
+    /// Where the change occurs, like `GET /a/{id} response 200 'name`
+    pub location: String,
+    pub message: String,
+}
+
+impl Change {
+    pub fn is_breaking(&self) -> bool {
+        self.severity == Severity::Breaking
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.location, self.message)
+    }
+}
+
+/// The direction in which a schema is transferred.
+///
+/// Narrowing a request schema breaks clients, as does widening a response schema.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum Direction {
+    Request,
+    Response,
+}
+
+/// How the set of values accepted by a schema changed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Bound {
+    Narrowed,
+    Widened,
+    Changed,
+}
+
+struct Differ<'a> {
+    old: &'a Spec,
+    new: &'a Spec,
+    changes: Vec<Change>,
+    /// The pairs of references already compared in each direction
+    visited: HashSet<(Ident, Ident, Direction)>,
+}
+
+impl<'a> Differ<'a> {
+    fn push<S: Into<String>>(&mut self, severity: Severity, loc: &str, msg: S) {
+        self.changes.push(Change {
+            severity,
+            location: loc.trim().to_owned(),
+            message: msg.into(),
+        })
+    }
+
+    fn breaking<S: Into<String>>(&mut self, loc: &str, msg: S) {
+        self.push(Severity::Breaking, loc, msg)
+    }
+
+    fn non_breaking<S: Into<String>>(&mut self, loc: &str, msg: S) {
+        self.push(Severity::NonBreaking, loc, msg)
+    }
+
+    /// Records a change to the values accepted by a schema, breaking depending on the direction.
+    fn bound<S: Into<String>>(&mut self, loc: &str, dir: Direction, b: Bound, msg: S) {
+        let severity = match (b, dir) {
+            (Bound::Changed, _)
+            | (Bound::Narrowed, Direction::Request)
+            | (Bound::Widened, Direction::Response) => Severity::Breaking,
+            _ => Severity::NonBreaking,
+        };
+        self.push(severity, loc, msg)
+    }
+
+    /// Records a change to a piece of documentation.
+    fn text(&mut self, loc: &str, what: &str, old: &Option<String>, new: &Option<String>) {
+        if old != new {
+            self.non_breaking(loc, format!("{} changed", what))
+        }
+    }
+
+    fn relations(&mut self) {
+        let (old, new) = (self.old, self.new);
+        for (pattern, o) in old.rels.iter() {
+            match new.rels.values().find(|n| shape(n) == shape(o)) {
+                Some(n) => self.relation(pattern, o, n),
+                None => self.breaking(pattern, "path removed"),
+            }
+        }
+        for (pattern, n) in new.rels.iter() {
+            if !old.rels.values().any(|o| shape(o) == shape(n)) {
+                self.non_breaking(pattern, "path added")
+            }
+        }
+    }
+
+    fn relation(&mut self, loc: &str, old: &Relation, new: &Relation) {
+        // Relations are matched by path shape, so variables are paired in order whatever their name.
+        for (o, n) in path_vars(old).zip(path_vars(new)) {
+            let loc = format!("{} path '{}", loc, o.name);
+            if o.name != n.name {
+                self.non_breaking(&loc, format!("renamed to '{}", n.name))
+            }
+            self.schema(&loc, Direction::Request, &o.schema, &n.schema);
+        }
+
+        for (m, o) in old.xfers.iter() {
+            let loc = format!("{} {}", method_name(m), loc);
+            match (o, &new.xfers[m]) {
+                (Some(o), Some(n)) => {
+                    self.params(
+                        &format!("{} query", loc),
+                        Direction::Request,
+                        &query_params(old, o),
+                        &query_params(new, n),
+                    );
+                    self.transfer(&loc, o, n)
+                }
+                (Some(_), None) => self.breaking(&loc, "method removed"),
+                (None, Some(_)) => self.non_breaking(&loc, "method added"),
+                (None, None) => {}
+            }
+        }
+    }
+
+    fn transfer(&mut self, loc: &str, old: &Transfer, new: &Transfer) {
+        if old.id != new.id {
+            self.breaking(loc, "operation identifier changed")
+        }
+        self.text(loc, "summary", &old.summary, &new.summary);
+        self.text(loc, "description", &old.desc, &new.desc);
+        if old.tags != new.tags {
+            self.non_breaking(loc, "tags changed")
+        }
+        let req = format!("{} request", loc);
+        self.content(&req, Direction::Request, &old.domain, &new.domain);
+        self.ranges(loc, &old.ranges, &new.ranges);
+    }
+
+    fn ranges(&mut self, loc: &str, old: &Ranges, new: &Ranges) {
+        for ((status, media), o) in old.iter() {
+            let loc = format!("{} {}", loc, response_name(status, media));
+            match new.get(&(*status, media.clone())) {
+                Some(n) => self.content(&loc, Direction::Response, o, n),
+                None => self.breaking(&loc, "response removed"),
+            }
+        }
+        for (status, media) in new.keys() {
+            if !old.contains_key(&(*status, media.clone())) {
+                let loc = format!("{} {}", loc, response_name(status, media));
+                self.non_breaking(&loc, "response added")
+            }
+        }
+    }
+
+    fn content(&mut self, loc: &str, dir: Direction, old: &Content, new: &Content) {
+        self.text(loc, "description", &old.desc, &new.desc);
+        if old.media != new.media {
+            self.breaking(loc, "media type changed")
+        }
+        match (&old.schema, &new.schema) {
+            (Some(o), Some(n)) => self.schema(loc, dir, o, n),
+            (Some(_), None) => self.bound(loc, dir, Bound::Widened, "body removed"),
+            (None, Some(_)) => self.bound(loc, dir, Bound::Narrowed, "body added"),
+            (None, None) => {}
+        }
+        let empty = Object::default();
+        self.params(
+            &format!("{} header", loc),
+            dir,
+            old.headers.as_ref().unwrap_or(&empty),
+            new.headers.as_ref().unwrap_or(&empty),
+        );
+    }
+
+    /// Compares parameters or headers, as properties required on their own.
+    fn params(&mut self, loc: &str, dir: Direction, old: &Object, new: &Object) {
+        for o in old.props.iter() {
+            let loc = format!("{} '{}", loc, o.name);
+            match new.props.iter().find(|n| n.name == o.name) {
+                Some(n) => {
+                    let (was, is) = (o.required.unwrap_or(false), n.required.unwrap_or(false));
+                    if !was && is {
+                        self.bound(&loc, dir, Bound::Narrowed, "became required")
+                    } else if was && !is {
+                        self.bound(&loc, dir, Bound::Widened, "became optional")
+                    }
+                    self.text(&loc, "description", &o.desc, &n.desc);
+                    self.schema(&loc, dir, &o.schema, &n.schema)
+                }
+                None => self.breaking(&loc, "removed"),
+            }
+        }
+        for n in new.props.iter() {
+            if !old.props.iter().any(|o| o.name == n.name) {
+                let loc = format!("{} '{}", loc, n.name);
+                if n.required.unwrap_or(false) {
+                    self.bound(&loc, dir, Bound::Narrowed, "required and added")
+                } else {
+                    self.non_breaking(&loc, "added")
+                }
+            }
+        }
+    }
+
+    /// Compares the properties of object schemas.
+    fn object(&mut self, loc: &str, dir: Direction, old: &Object, new: &Object) {
+        for o in old.props.iter() {
+            let loc = format!("{} '{}", loc, o.name);
+            match new.props.iter().find(|n| n.name == o.name) {
+                Some(n) => {
+                    let was = o.schema.required.unwrap_or(false);
+                    let is = n.schema.required.unwrap_or(false);
+                    if !was && is {
+                        self.bound(&loc, dir, Bound::Narrowed, "property became required")
+                    } else if was && !is {
+                        self.bound(&loc, dir, Bound::Widened, "property became optional")
+                    }
+                    self.schema(&loc, dir, &o.schema, &n.schema)
+                }
+                None => self.bound(&loc, dir, Bound::Widened, "property removed"),
+            }
+        }
+        for n in new.props.iter() {
+            if !old.props.iter().any(|o| o.name == n.name) {
+                let loc = format!("{} '{}", loc, n.name);
+                if n.schema.required.unwrap_or(false) {
+                    self.bound(&loc, dir, Bound::Narrowed, "required property added")
+                } else {
+                    self.non_breaking(&loc, "property added")
+                }
+            }
+        }
+    }
+
+    fn schema(&mut self, loc: &str, dir: Direction, old: &Schema, new: &Schema) {
+        // References are compared once per direction, where they are declared.
+        if let (SchemaExpr::Ref(o), SchemaExpr::Ref(n)) = (&old.expr, &new.expr) {
+            if self.visited.insert((o.clone(), n.clone(), dir)) {
+                if let (Some(Reference::Schema(os)), Some(Reference::Schema(ns))) =
+                    (self.old.refs.get(o), self.new.refs.get(n))
+                {
+                    let loc = if o == n {
+                        format!("{} {}", direction_name(dir), o)
+                    } else {
+                        loc.to_owned()
+                    };
+                    self.schema(&loc, dir, os, ns)
+                } else if o != n {
+                    self.bound(loc, dir, Bound::Changed, "reference changed")
+                }
+            }
+            return;
+        }
+
+        // A reference on one side only is compared by value.
+        let old = match &old.expr {
+            SchemaExpr::Ref(o) => match self.old.refs.get(o) {
+                Some(Reference::Schema(s)) => s,
+                None => return self.bound(loc, dir, Bound::Changed, "reference replaced"),
+            },
+            _ => old,
+        };
+        let new = match &new.expr {
+            SchemaExpr::Ref(n) => match self.new.refs.get(n) {
+                Some(Reference::Schema(s)) => s,
+                None => return self.bound(loc, dir, Bound::Changed, "reference introduced"),
+            },
+            _ => new,
+        };
+
+        self.text(loc, "title", &old.title, &new.title);
+        self.text(loc, "description", &old.desc, &new.desc);
+
+        match (&old.expr, &new.expr) {
+            (SchemaExpr::Num(o), SchemaExpr::Num(n)) => {
+                self.limit(loc, dir, "minimum", o.minimum, n.minimum, true);
+                self.limit(loc, dir, "maximum", o.maximum, n.maximum, false);
+                self.multiple(loc, dir, o.multiple_of, n.multiple_of);
+            }
+            (SchemaExpr::Int(o), SchemaExpr::Int(n)) => {
+                self.limit(loc, dir, "minimum", o.minimum, n.minimum, true);
+                self.limit(loc, dir, "maximum", o.maximum, n.maximum, false);
+                self.multiple(loc, dir, o.multiple_of, n.multiple_of);
+            }
+            (SchemaExpr::Str(o), SchemaExpr::Str(n)) => {
+                match (&o.pattern, &n.pattern) {
+                    (None, Some(_)) => self.bound(loc, dir, Bound::Narrowed, "pattern added"),
+                    (Some(_), None) => self.bound(loc, dir, Bound::Widened, "pattern removed"),
+                    (Some(a), Some(b)) if a != b => {
+                        self.bound(loc, dir, Bound::Changed, "pattern changed")
+                    }
+                    _ => {}
+                }
+                let (oe, ne) = (&o.enumeration, &n.enumeration);
+                if oe.is_empty() && !ne.is_empty() {
+                    self.bound(loc, dir, Bound::Narrowed, "enumeration added")
+                } else if !oe.is_empty() && ne.is_empty() {
+                    self.bound(loc, dir, Bound::Widened, "enumeration removed")
+                } else {
+                    let removed: Vec<_> = oe.iter().filter(|v| !ne.contains(v)).cloned().collect();
+                    let added: Vec<_> = ne.iter().filter(|v| !oe.contains(v)).cloned().collect();
+                    if !removed.is_empty() {
+                        let msg = format!("enumeration values removed: {}", removed.join(", "));
+                        self.bound(loc, dir, Bound::Narrowed, msg)
+                    }
+                    if !added.is_empty() {
+                        let msg = format!("enumeration values added: {}", added.join(", "));
+                        self.bound(loc, dir, Bound::Widened, msg)
+                    }
+                }
+            }
+            (SchemaExpr::Bool(_), SchemaExpr::Bool(_))
+            | (SchemaExpr::Rel(_), SchemaExpr::Rel(_)) => {}
+            (SchemaExpr::Uri(o), SchemaExpr::Uri(n)) => {
+                if o.pattern() != n.pattern() {
+                    self.bound(loc, dir, Bound::Changed, "URI template changed")
+                }
+            }
+            (SchemaExpr::Array(o), SchemaExpr::Array(n)) => {
+                self.schema(&format!("{} []", loc), dir, &o.item, &n.item)
+            }
+            (SchemaExpr::Object(o), SchemaExpr::Object(n)) => self.object(loc, dir, o, n),
+            (SchemaExpr::Op(o), SchemaExpr::Op(n)) if o.op == n.op => {
+                let (pairs, removed, added) = pair_operands(&o.schemas, &n.schemas);
+                for (i, j) in pairs {
+                    self.schema(
+                        &format!("{} ({})", loc, i + 1),
+                        dir,
+                        &o.schemas[i],
+                        &n.schemas[j],
+                    )
+                }
+                // More alternatives accept more values, more joined schemas fewer.
+                let (more, fewer) = match o.op {
+                    Operator::Join => (Bound::Narrowed, Bound::Widened),
+                    _ => (Bound::Widened, Bound::Narrowed),
+                };
+                if added > 0 {
+                    self.bound(loc, dir, more, "schemas added")
+                } else if removed > 0 {
+                    self.bound(loc, dir, fewer, "schemas removed")
+                }
+            }
+            (SchemaExpr::Int(_), SchemaExpr::Num(_)) => self.bound(
+                loc,
+                dir,
+                Bound::Widened,
+                "type changed from integer to number",
+            ),
+            (SchemaExpr::Num(_), SchemaExpr::Int(_)) => self.bound(
+                loc,
+                dir,
+                Bound::Narrowed,
+                "type changed from number to integer",
+            ),
+            (o, n) => {
+                let msg = format!("type changed from {} to {}", kind_name(o), kind_name(n));
+                self.bound(loc, dir, Bound::Changed, msg)
+            }
+        }
+    }
+
+    /// Compares a lower or upper limit on numeric values.
+    fn limit<T: PartialOrd + Display>(
+        &mut self,
+        loc: &str,
+        dir: Direction,
+        name: &str,
+        old: Option<T>,
+        new: Option<T>,
+        lower: bool,
+    ) {
+        match (old, new) {
+            (None, Some(n)) => {
+                self.bound(loc, dir, Bound::Narrowed, format!("{} {} added", name, n))
+            }
+            (Some(_), None) => self.bound(loc, dir, Bound::Widened, format!("{} removed", name)),
+            (Some(o), Some(n)) if o != n => {
+                let b = if (n > o) == lower {
+                    Bound::Narrowed
+                } else {
+                    Bound::Widened
+                };
+                self.bound(loc, dir, b, format!("{} changed from {} to {}", name, o, n))
+            }
+            _ => {}
+        }
+    }
+
+    fn multiple<T: PartialEq + Display>(
+        &mut self,
+        loc: &str,
+        dir: Direction,
+        old: Option<T>,
+        new: Option<T>,
+    ) {
+        match (old, new) {
+            (None, Some(n)) => self.bound(
+                loc,
+                dir,
+                Bound::Narrowed,
+                format!("multiple of {} added", n),
+            ),
+            (Some(_), None) => self.bound(loc, dir, Bound::Widened, "multiple removed"),
+            (Some(o), Some(n)) if o != n => {
+                let msg = format!("multiple changed from {} to {}", o, n);
+                self.bound(loc, dir, Bound::Changed, msg)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Pairs the operands of two versions of an operation, regardless of their order.
+///
+/// Operands are paired when identical, then when referencing the same declaration, then when
+/// of the same kind, and finally in order. Returns the pairs of indices, along with the number
+/// of old and new operands left unpaired.
+fn pair_operands(old: &[Schema], new: &[Schema]) -> (Vec<(usize, usize)>, usize, usize) {
+    let criteria: [fn(&Schema, &Schema) -> bool; 4] = [
+        |o, n| o == n,
+        |o, n| matches!((&o.expr, &n.expr), (SchemaExpr::Ref(a), SchemaExpr::Ref(b)) if a == b),
+        |o, n| kind_name(&o.expr) == kind_name(&n.expr),
+        |_, _| true,
+    ];
+    let mut pairs = Vec::new();
+    let mut old_left: Vec<usize> = (0..old.len()).collect();
+    let mut new_left: Vec<usize> = (0..new.len()).collect();
+    for same in criteria {
+        old_left.retain(
+            |&i| match new_left.iter().position(|&j| same(&old[i], &new[j])) {
+                Some(k) => {
+                    pairs.push((i, new_left.remove(k)));
+                    false
+                }
+                None => true,
+            },
+        );
+    }
+    pairs.sort();
+    (pairs, old_left.len(), new_left.len())
+}
+
+/// Returns the path of a relation with anonymous variables, as in `/a/{}`.
+fn shape(rel: &Relation) -> String {
+    let path: String = rel
+        .uri
+        .path
+        .iter()
+        .map(|s| match s {
+            UriSegment::Literal(l) => format!("/{}", l),
+            UriSegment::Variable(_) => "/{}".to_owned(),
+        })
+        .collect();
+    if path.is_empty() {
+        "/".to_owned()
+    } else {
+        path
+    }
+}
+
+fn path_vars(rel: &Relation) -> impl Iterator<Item = &Property> {
+    rel.uri.path.iter().filter_map(|s| match s {
+        UriSegment::Variable(p) => Some(p.as_ref()),
+        UriSegment::Literal(_) => None,
+    })
+}
+
+/// Returns the query parameters of a transfer, including those of the relation URI.
+fn query_params(rel: &Relation, xfer: &Transfer) -> Object {
+    let uri_params = rel.uri.params.iter().flat_map(|p| p.props.iter());
+    let params = xfer.params.iter().flat_map(|p| p.props.iter());
+    Object {
+        props: uri_params.chain(params).cloned().collect(),
+    }
+}
+
+fn method_name(m: Method) -> &'static str {
+    match m {
+        Method::Get => "GET",
+        Method::Put => "PUT",
+        Method::Post => "POST",
+        Method::Patch => "PATCH",
+        Method::Delete => "DELETE",
+        Method::Options => "OPTIONS",
+        Method::Head => "HEAD",
+    }
+}
+
+fn direction_name(dir: Direction) -> &'static str {
+    match dir {
+        Direction::Request => "request",
+        Direction::Response => "response",
+    }
+}
+
+fn response_name(status: &Option<HttpStatus>, media: &Option<String>) -> String {
+    let status = match status {
+        Some(HttpStatus::Code(c)) => c.to_string(),
+        Some(HttpStatus::Range(r)) => match r {
+            HttpStatusRange::Info => "1XX",
+            HttpStatusRange::Success => "2XX",
+            HttpStatusRange::Redirect => "3XX",
+            HttpStatusRange::ClientError => "4XX",
+            HttpStatusRange::ServerError => "5XX",
+        }
+        .to_owned(),
+        None => "default".to_owned(),
+    };
+    match media {
+        Some(m) => format!("response {} {}", status, m),
+        None => format!("response {}", status),
+    }
+}
+
+fn kind_name(e: &SchemaExpr) -> &'static str {
+    match e {
+        SchemaExpr::Num(_) => "number",
+        SchemaExpr::Str(_) => "string",
+        SchemaExpr::Bool(_) => "boolean",
+        SchemaExpr::Int(_) => "integer",
+        SchemaExpr::Rel(_) => "relation",
+        SchemaExpr::Uri(_) => "URI",
+        SchemaExpr::Array(_) => "array",
+        SchemaExpr::Object(_) => "object",
+        SchemaExpr::Op(op) => match op.op {
+            Operator::Join => "join",
+            Operator::Any => "any of",
+            Operator::Sum => "one of",
+            Operator::Range => "ranges",
+        },
+        SchemaExpr::Ref(_) => "reference",
+    }
+}
+
+/// Compares two versions of a specification.
+///
+/// Relations are matched by path, whatever the names of their variables, transfers by method
+/// and ranges by status and media type. The operands of schema operations are matched
+/// regardless of their order. Parameters, bodies and referenced schemas are compared where
+/// they are used: a change breaks clients if it removes something they rely on, or if it narrows the values
+/// accepted in a request or widens the values returned in a response.
+pub fn diff(old: &Spec, new: &Spec) -> Vec<Change> {
+    let mut differ = Differ {
+        old,
+        new,
+        changes: Vec::new(),
+        visited: HashSet::new(),
+    };
+    differ.relations();
+    differ.changes
+}
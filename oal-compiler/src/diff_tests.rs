@@ -0,0 +1,180 @@
+use crate::diff::{diff, Change, Severity};
+use crate::tests::eval;
+
+fn changes(old: &str, new: &str) -> anyhow::Result<Vec<(Severity, String, String)>> {
+    let changes = diff(&eval(old)?, &eval(new)?)
+        .into_iter()
+        .map(
+            |Change {
+                 severity,
+                 location,
+                 message,
+             }| (severity, location, message),
+        )
+        .collect();
+    anyhow::Ok(changes)
+}
+
+fn change(severity: Severity, location: &str, message: &str) -> (Severity, String, String) {
+    (severity, location.to_owned(), message.to_owned())
+}
+
+#[test]
+fn diff_unchanged() -> anyhow::Result<()> {
+    let code = r#"
+        let @a = { 'id int, 'name str };
+        res /a/{ 'id int }?{ 'q str } ( get -> @a, put : @a -> <status=204,> );
+    "#;
+
+    assert!(changes(code, code)?.is_empty());
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn diff_breaking() -> anyhow::Result<()> {
+    let old = r#"
+        let s = str `enum: [on, off]`;
+        res /a ( get { 'q str } : { 's s } -> <status=200, {}> :: <status=404,>, put -> {} );
+        res /b ( get -> {} );
+    "#;
+    let new = r#"
+        let s = str `enum: [on]`;
+        # required: true
+        let r = 'r str;
+        res /a ( get { 'q str, r } : { 's s } -> <status=200, {}> );
+    "#;
+
+    assert_eq!(
+        changes(old, new)?,
+        vec![
+            change(Severity::Breaking, "GET /a query 'r", "required and added"),
+            change(
+                Severity::Breaking,
+                "GET /a request 's",
+                "enumeration values removed: off"
+            ),
+            change(
+                Severity::Breaking,
+                "GET /a response 404",
+                "response removed"
+            ),
+            change(Severity::Breaking, "PUT /a", "method removed"),
+            change(Severity::Breaking, "/b", "path removed"),
+        ]
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn diff_non_breaking() -> anyhow::Result<()> {
+    let old = r#"
+        # summary: "gets a"
+        let op = get : { 's str `enum: [on]` } -> { 'n int `minimum: 0` };
+        res /a ( op );
+    "#;
+    let new = r#"
+        # summary: "gets a, really"
+        let op = get { 'q str } : { 's str `enum: [on, off]` } -> { 'n int `minimum: 1` };
+        res /a ( op, put -> {} );
+        res /b ( get -> {} );
+    "#;
+
+    let changes = changes(old, new)?;
+
+    assert!(changes.iter().all(|(s, _, _)| *s == Severity::NonBreaking));
+    assert_eq!(
+        changes
+            .iter()
+            .map(|(_, l, m)| format!("{}: {}", l, m))
+            .collect::<Vec<_>>(),
+        vec![
+            "GET /a query 'q: added",
+            "GET /a: summary changed",
+            "GET /a request 's: enumeration values added: off",
+            "GET /a response default 'n: minimum changed from 0 to 1",
+            "PUT /a: method added",
+            "/b: path added",
+        ]
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn diff_references() -> anyhow::Result<()> {
+    let old = r#"
+        let @a = { 'id int, 'tag str };
+        res /a ( get -> @a, post : @a -> <status=201,> );
+        res /b ( get -> @a );
+    "#;
+    let new = r#"
+        let @a = { 'id int };
+        res /a ( get -> @a, post : @a -> <status=201,> );
+        res /b ( get -> @a );
+    "#;
+
+    assert_eq!(
+        changes(old, new)?,
+        vec![
+            change(Severity::Breaking, "response @a 'tag", "property removed"),
+            change(Severity::NonBreaking, "request @a 'tag", "property removed"),
+        ]
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn diff_reordered_operands() -> anyhow::Result<()> {
+    let old = r#"
+        let @a = { 'id int };
+        let @b = { 'name str };
+        res /a ( put : (@a | @b) -> (@a | @b | { 's str `enum: [x]` }) );
+    "#;
+    let new = r#"
+        let @a = { 'id int };
+        let @b = { 'name str };
+        res /a ( put : (@b | @a) -> ({ 's str `enum: [x, y]` } | @b | @a) );
+    "#;
+
+    assert_eq!(
+        changes(old, new)?,
+        vec![change(
+            Severity::Breaking,
+            "PUT /a response default (3) 's",
+            "enumeration values added: y"
+        )]
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn diff_renamed_path_variable() -> anyhow::Result<()> {
+    let old = r#"
+        res /items/{ 'id int } ( get -> {} );
+    "#;
+    let new = r#"
+        res /items/{ 'itemId int `minimum: 1` } ( get -> {} );
+    "#;
+
+    assert_eq!(
+        changes(old, new)?,
+        vec![
+            change(
+                Severity::NonBreaking,
+                "/items/{id} path 'id",
+                "renamed to 'itemId"
+            ),
+            change(
+                Severity::Breaking,
+                "/items/{id} path 'id",
+                "minimum 1 added"
+            ),
+        ]
+    );
+
+    anyhow::Ok(())
+}
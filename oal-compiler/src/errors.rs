@@ -15,6 +15,8 @@ pub enum Kind {
     InvalidURL,
     InvalidHttpStatus,
     InvalidSyntax,
+    UnknownScheme,
+    NotFound,
 }
 
 impl Default for Kind {
@@ -1,4 +1,5 @@
 use crate::annotation::{Annotated, Annotation};
+use crate::locator::{Located, Locator};
 use crate::reduction::Semigroup;
 use crate::tag::{Tag, Tagged};
 use oal_syntax::ast::{AsMutNode, AsRefNode, NodeExpr};
@@ -7,6 +8,7 @@ use oal_syntax::ast::{AsMutNode, AsRefNode, NodeExpr};
 pub struct TypedExpr {
     tag: Option<Tag>,
     ann: Option<Annotation>,
+    loc: Option<Locator>,
     inner: NodeExpr<TypedExpr>,
 }
 
@@ -39,11 +41,22 @@ impl Annotated for TypedExpr {
     }
 }
 
+impl Located for TypedExpr {
+    fn locator(&self) -> Option<&Locator> {
+        self.loc.as_ref()
+    }
+
+    fn set_locator(&mut self, l: Locator) {
+        self.loc = Some(l)
+    }
+}
+
 impl From<NodeExpr<TypedExpr>> for TypedExpr {
     fn from(e: NodeExpr<TypedExpr>) -> Self {
         TypedExpr {
             tag: None,
             ann: None,
+            loc: None,
             inner: e,
         }
     }
@@ -66,6 +79,7 @@ impl Semigroup for TypedExpr {
     fn combine(&mut self, with: Self) {
         self.inner = with.inner;
         self.tag = with.tag;
+        self.loc = with.loc;
         if let Some(ann) = &mut self.ann {
             if let Some(other) = with.ann {
                 ann.extend(other);
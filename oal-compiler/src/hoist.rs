@@ -0,0 +1,259 @@
+//! A pass over a specification to hoist anonymous schemas into references.
+
+use crate::spec::{
+    Content, Object, Reference, Relation, Schema, SchemaExpr, Spec, Transfer, UriSegment,
+};
+use oal_syntax::atom;
+use oal_syntax::atom::Ident;
+
+/// The criteria for hoisting an anonymous schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hoisting {
+    /// The minimum number of structurally identical uses
+    pub min_uses: usize,
+    /// The size above which a schema is hoisted even if used once
+    pub max_size: Option<usize>,
+}
+
+impl Default for Hoisting {
+    fn default() -> Self {
+        Hoisting {
+            min_uses: 2,
+            max_size: None,
+        }
+    }
+}
+
+/// A group of structurally identical anonymous schemas.
+struct Candidate {
+    expr: SchemaExpr,
+    name: String,
+    uses: usize,
+}
+
+/// Converts a piece of text into a Pascal-cased identifier.
+fn pascal_case(s: &str) -> String {
+    s.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Counts the schema nodes of an expression.
+fn size(e: &SchemaExpr) -> usize {
+    1 + match e {
+        SchemaExpr::Array(a) => size(&a.item.expr),
+        SchemaExpr::Object(o) => o.props.iter().map(|p| size(&p.schema.expr)).sum(),
+        SchemaExpr::Op(op) => op.schemas.iter().map(|s| size(&s.expr)).sum(),
+        _ => 0,
+    }
+}
+
+/// Tells whether an expression is a candidate for hoisting.
+fn is_hoistable(e: &SchemaExpr) -> bool {
+    match e {
+        SchemaExpr::Object(o) => !o.props.is_empty(),
+        SchemaExpr::Op(_) => true,
+        _ => false,
+    }
+}
+
+/// Visits a schema and its children, in pre-order, with a name derived from the context.
+///
+/// Children are only visited if the visitor returns true.
+fn walk_schema<F>(s: &mut Schema, name: &str, f: &mut F)
+where
+    F: FnMut(&mut Schema, &str) -> bool,
+{
+    if f(s, name) {
+        match &mut s.expr {
+            SchemaExpr::Array(a) => walk_schema(&mut a.item, &format!("{}Item", name), f),
+            SchemaExpr::Object(o) => walk_object(o, f),
+            SchemaExpr::Op(op) => op.schemas.iter_mut().for_each(|s| walk_schema(s, name, f)),
+            _ => {}
+        }
+    }
+}
+
+fn walk_object<F>(o: &mut Object, f: &mut F)
+where
+    F: FnMut(&mut Schema, &str) -> bool,
+{
+    o.props
+        .iter_mut()
+        .for_each(|p| walk_schema(&mut p.schema, &pascal_case(p.name.as_ref()), f));
+}
+
+fn walk_content<F>(c: &mut Content, name: &str, f: &mut F)
+where
+    F: FnMut(&mut Schema, &str) -> bool,
+{
+    if let Some(s) = c.schema.as_mut() {
+        walk_schema(s, name, f);
+    }
+    if let Some(h) = c.headers.as_mut() {
+        walk_object(h, f);
+    }
+}
+
+fn walk_transfer<F>(x: &mut Transfer, name: &str, f: &mut F)
+where
+    F: FnMut(&mut Schema, &str) -> bool,
+{
+    walk_content(&mut x.domain, &format!("{}Request", name), f);
+    x.ranges
+        .values_mut()
+        .for_each(|c| walk_content(c, &format!("{}Response", name), f));
+    if let Some(p) = x.params.as_mut() {
+        walk_object(p, f);
+    }
+}
+
+fn walk_relation<F>(rel: &mut Relation, f: &mut F)
+where
+    F: FnMut(&mut Schema, &str) -> bool,
+{
+    let path: String = rel
+        .uri
+        .path
+        .iter()
+        .map(|s| match s {
+            UriSegment::Literal(l) => pascal_case(l),
+            UriSegment::Variable(_) => String::new(),
+        })
+        .collect();
+    for s in rel.uri.path.iter_mut() {
+        if let UriSegment::Variable(p) = s {
+            walk_schema(&mut p.schema, &pascal_case(p.name.as_ref()), f);
+        }
+    }
+    if let Some(p) = rel.uri.params.as_mut() {
+        walk_object(p, f);
+    }
+    for (m, x) in rel.xfers.iter_mut() {
+        if let Some(x) = x {
+            walk_transfer(x, &format!("{}{}", method_name(m), path), f);
+        }
+    }
+}
+
+fn method_name(m: atom::Method) -> &'static str {
+    match m {
+        atom::Method::Get => "Get",
+        atom::Method::Put => "Put",
+        atom::Method::Post => "Post",
+        atom::Method::Patch => "Patch",
+        atom::Method::Delete => "Delete",
+        atom::Method::Options => "Options",
+        atom::Method::Head => "Head",
+    }
+}
+
+/// Visits all the schemas of a specification.
+///
+/// The top-level schemas of references are not visited, only their children.
+fn walk_spec<F>(spec: &mut Spec, f: &mut F)
+where
+    F: FnMut(&mut Schema, &str) -> bool,
+{
+    spec.rels.values_mut().for_each(|rel| walk_relation(rel, f));
+    for (name, r) in spec.refs.iter_mut() {
+        let Reference::Schema(s) = r;
+        let ref_name = pascal_case(&name.untagged());
+        let mut top = true;
+        walk_schema(s, &ref_name, &mut |s, n| {
+            std::mem::take(&mut top) || f(s, n)
+        });
+    }
+}
+
+/// Allocates a reference name not yet in use.
+fn unique_name(spec: &Spec, taken: &[Ident], name: &str) -> Ident {
+    let name = if name.is_empty() { "Schema" } else { name };
+    (1..)
+        .map(|i| {
+            if i == 1 {
+                Ident::from(format!("@{}", name).as_str())
+            } else {
+                Ident::from(format!("@{}{}", name, i).as_str())
+            }
+        })
+        .find(|id| {
+            !taken.contains(id)
+                && !spec
+                    .refs
+                    .keys()
+                    .any(|r| r.untagged().eq_ignore_ascii_case(&id.untagged()))
+        })
+        .unwrap()
+}
+
+/// Hoists anonymous schemas into references.
+///
+/// A schema is hoisted if structurally identical schemas are used more than once,
+/// or if its size is above the given threshold.
+/// The reference name is derived from the declaration, property or operation the first
+/// occurrence of the schema comes from.
+pub fn hoist(spec: &mut Spec, opts: &Hoisting) {
+    let mut candidates: Vec<Candidate> = Vec::new();
+
+    walk_spec(spec, &mut |s, name| {
+        if is_hoistable(&s.expr) {
+            match candidates.iter_mut().find(|c| c.expr == s.expr) {
+                Some(c) => c.uses += 1,
+                None => candidates.push(Candidate {
+                    expr: s.expr.clone(),
+                    name: name.to_owned(),
+                    uses: 1,
+                }),
+            }
+        }
+        true
+    });
+
+    let mut hoisted: Vec<(SchemaExpr, Ident)> = Vec::new();
+    let mut taken = Vec::new();
+    for c in candidates {
+        let large = matches!(opts.max_size, Some(max) if size(&c.expr) > max);
+        if c.uses >= opts.min_uses || large {
+            let id = unique_name(spec, &taken, &c.name);
+            taken.push(id.clone());
+            hoisted.push((c.expr, id));
+        }
+    }
+
+    if hoisted.is_empty() {
+        return;
+    }
+
+    let mut replace = |s: &mut Schema, _: &str| match hoisted.iter().find(|(e, _)| *e == s.expr) {
+        Some((_, id)) => {
+            s.expr = SchemaExpr::Ref(id.clone());
+            false
+        }
+        None => true,
+    };
+
+    walk_spec(spec, &mut replace);
+
+    for (expr, id) in hoisted.iter() {
+        let mut schema = Schema {
+            expr: expr.clone(),
+            desc: None,
+            title: None,
+            required: None,
+        };
+        // Only the children of the hoisted schema itself can be replaced.
+        let mut top = true;
+        walk_schema(&mut schema, "", &mut |s, n| {
+            std::mem::take(&mut top) || replace(s, n)
+        });
+        spec.refs.insert(id.clone(), Reference::Schema(schema));
+    }
+}
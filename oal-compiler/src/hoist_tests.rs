@@ -0,0 +1,98 @@
+use crate::hoist::{hoist, Hoisting};
+use crate::spec::{Reference, SchemaExpr};
+use crate::tests::eval;
+use oal_syntax::atom::{Ident, Method};
+
+#[test]
+fn hoist_repeated() -> anyhow::Result<()> {
+    let code = r#"
+        let @a = { 'address { 'city str, 'zip str } };
+        res /a ( get -> @a );
+        res /b ( get -> { 'address { 'city str, 'zip str } } );
+    "#;
+
+    let mut spec = eval(code)?;
+
+    hoist(&mut spec, &Hoisting::default());
+
+    let name = Ident::from("@Address");
+
+    match spec.refs.get(&name) {
+        Some(Reference::Schema(s)) => assert!(matches!(s.expr, SchemaExpr::Object(_))),
+        None => panic!("expected hoisted reference"),
+    }
+
+    match spec.refs.get(&Ident::from("@a")) {
+        Some(Reference::Schema(s)) => match &s.expr {
+            SchemaExpr::Object(o) => {
+                assert_eq!(o.props[0].schema.expr, SchemaExpr::Ref(name.clone()))
+            }
+            _ => panic!("expected object"),
+        },
+        None => panic!("expected reference"),
+    }
+
+    let xfer = spec.rels["/b"].xfers[Method::Get]
+        .as_ref()
+        .expect("expected transfer");
+    let range = xfer.ranges.values().next().unwrap();
+    match &range.schema.as_ref().unwrap().expr {
+        SchemaExpr::Object(o) => assert_eq!(o.props[0].schema.expr, SchemaExpr::Ref(name)),
+        _ => panic!("expected object"),
+    }
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn hoist_large() -> anyhow::Result<()> {
+    let code = r#"
+        res /b ( get -> { 'x num, 'y num, 'z num } );
+    "#;
+
+    let mut spec = eval(code)?;
+
+    hoist(&mut spec, &Hoisting::default());
+
+    assert!(spec.refs.is_empty());
+
+    // The response object has four nodes, which is not above the threshold.
+    let opts = Hoisting {
+        max_size: Some(4),
+        ..Default::default()
+    };
+
+    hoist(&mut spec, &opts);
+
+    assert!(spec.refs.is_empty());
+
+    let opts = Hoisting {
+        max_size: Some(3),
+        ..Default::default()
+    };
+
+    hoist(&mut spec, &opts);
+
+    assert!(spec.refs.contains_key(&Ident::from("@GetBResponse")));
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn hoist_unique_names() -> anyhow::Result<()> {
+    let code = r#"
+        let @Item = {};
+        res /a ( get -> @Item );
+        res /b ( put : { 'item { 'x num } } -> { 'item { 'x num } } );
+        res /c ( put : { 'item { 'y num } } -> { 'item { 'y num } } );
+    "#;
+
+    let mut spec = eval(code)?;
+
+    hoist(&mut spec, &Hoisting::default());
+
+    assert!(spec.refs.contains_key(&Ident::from("@Item2")));
+    assert!(spec.refs.contains_key(&Ident::from("@Item3")));
+
+    anyhow::Ok(())
+}
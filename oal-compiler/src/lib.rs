@@ -2,13 +2,17 @@ extern crate core;
 
 mod annotation;
 mod compile;
+pub mod diff;
 mod errors;
 mod expr;
+pub mod hoist;
 mod inference;
+pub mod lint;
 mod locator;
 mod module;
 mod node;
 mod reduction;
+pub mod resolver;
 mod scan;
 mod scope;
 pub mod spec;
@@ -21,22 +25,33 @@ mod annotation_tests;
 #[cfg(test)]
 mod compile_tests;
 #[cfg(test)]
+mod diff_tests;
+#[cfg(test)]
+mod hoist_tests;
+#[cfg(test)]
 mod inference_tests;
 #[cfg(test)]
+mod lint_tests;
+#[cfg(test)]
 mod module_tests;
 #[cfg(test)]
 mod reduction_tests;
 #[cfg(test)]
+mod resolver_tests;
+#[cfg(test)]
 mod scope_tests;
 #[cfg(test)]
 mod spec_tests;
 #[cfg(test)]
+mod tests;
+#[cfg(test)]
 mod typecheck_tests;
 
 pub use crate::compile::compile;
 pub use crate::errors::Result;
-pub use crate::locator::Locator;
-pub use crate::module::load;
+pub use crate::locator::{Located, Locator};
+pub use crate::module::{load, load_all, load_with};
+pub use crate::tag::{Tag, Tagged};
 
 pub type Program = oal_syntax::ast::Program<expr::TypedExpr>;
 pub type ModuleSet = module::ModuleSet<expr::TypedExpr>;
@@ -0,0 +1,330 @@
+//! A lint engine checking programs against API design rules.
+
+use crate::annotation::Annotated;
+use crate::errors::{Error, Kind, Result};
+use crate::locator::{Located, Locator};
+use crate::module::ModuleSet;
+use crate::spec::Transfer;
+use oal_syntax::ast::{AsExpr, Expr, Operator, Statement, UriSegment};
+use oal_syntax::atom::{HttpStatus, HttpStatusRange};
+use oal_syntax::span::Span;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+/// The statement annotation suppressing rules, with a rule identifier or a list of them.
+pub const LINT_ALLOW: &str = "lint-allow";
+
+/// How the violations of a rule are reported.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Level::Allow => write!(f, "allowed"),
+            Level::Warn => write!(f, "warning"),
+            Level::Deny => write!(f, "error"),
+        }
+    }
+}
+
+/// A design rule.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rule {
+    pub id: &'static str,
+    /// The level applying unless configured otherwise
+    pub level: Level,
+    pub desc: &'static str,
+}
+
+pub const TRANSFER_SUMMARY: &str = "transfer-summary";
+pub const PATH_KEBAB_CASE: &str = "path-kebab-case";
+pub const SCHEMA_PASCAL_CASE: &str = "schema-pascal-case";
+pub const ERROR_RANGES: &str = "error-ranges";
+pub const NO_ANY: &str = "no-any";
+
+/// The built-in rules.
+pub const RULES: &[Rule] = &[
+    Rule {
+        id: TRANSFER_SUMMARY,
+        level: Level::Warn,
+        desc: "every operation has a summary",
+    },
+    Rule {
+        id: PATH_KEBAB_CASE,
+        level: Level::Warn,
+        desc: "path segments are kebab-case",
+    },
+    Rule {
+        id: SCHEMA_PASCAL_CASE,
+        level: Level::Warn,
+        desc: "reference names are PascalCase",
+    },
+    Rule {
+        id: ERROR_RANGES,
+        level: Level::Warn,
+        desc: "every operation has 4XX and 5XX responses",
+    },
+    Rule {
+        id: NO_ANY,
+        level: Level::Warn,
+        desc: "schemas have no untyped alternative (~)",
+    },
+];
+
+/// A violation of a rule.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub rule: &'static str,
+    pub level: Level,
+    pub message: String,
+    /// The module in which the violation was found
+    pub module: Locator,
+    pub span: Option<Span>,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}[{}]: {}", self.level, self.rule, self.message)?;
+        match &self.span {
+            Some(span) => write!(f, "\n  --> {} {}", self.module, span),
+            None => write!(f, "\n  --> {}", self.module),
+        }
+    }
+}
+
+/// The rules to check, each at a given level.
+#[derive(Clone, Debug)]
+pub struct Linter {
+    levels: HashMap<&'static str, Level>,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Linter {
+            levels: RULES.iter().map(|r| (r.id, r.level)).collect(),
+        }
+    }
+}
+
+impl Linter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the level of a rule.
+    pub fn with_level(mut self, id: &str, level: Level) -> Result<Self> {
+        let rule = RULES
+            .iter()
+            .find(|r| r.id == id)
+            .ok_or_else(|| Error::new(Kind::NotFound, "unknown lint rule").with(&id))?;
+        self.levels.insert(rule.id, level);
+        Ok(self)
+    }
+
+    /// Checks all the modules of a set.
+    ///
+    /// Modules are checked after the modules they import, and an expression inlined
+    /// from a declaration is only reported once, in the module declaring it.
+    /// A rule is suppressed within a declaration or resource annotated with `lint-allow`.
+    pub fn lint<T: AsExpr + Annotated + Located>(&self, mods: &ModuleSet<T>) -> Vec<Diagnostic> {
+        let mut order = Vec::new();
+        let mut locs: Vec<_> = mods.iter().map(|(l, _)| l).collect();
+        locs.sort_by_key(|l| l.url.as_str());
+        for loc in locs {
+            dependency_order(mods, loc, &mut order);
+        }
+
+        let mut lint = Lint {
+            linter: self,
+            diags: Vec::new(),
+            seen: HashSet::new(),
+            module: mods.base.clone(),
+        };
+        for loc in order {
+            let prg = mods.get(&loc).unwrap();
+            lint.module = loc;
+            for stmt in prg.stmts.iter() {
+                match stmt {
+                    Statement::Decl(d) => {
+                        let allowed = allowed(&[], &d.expr);
+                        if d.name.is_reference() && !is_pascal_case(&d.name.untagged()) {
+                            let msg = format!("reference name {} is not PascalCase", d.name);
+                            lint.report(SCHEMA_PASCAL_CASE, &allowed, &d.expr, msg);
+                        }
+                        lint.walk(&d.expr, &allowed)
+                    }
+                    Statement::Res(r) => lint.walk(&r.rel, &[]),
+                    _ => {}
+                }
+            }
+        }
+        lint.diags
+    }
+}
+
+fn dependency_order<T: AsExpr>(mods: &ModuleSet<T>, loc: &Locator, order: &mut Vec<Locator>) {
+    if order.contains(loc) {
+        return;
+    }
+    if let Some(prg) = mods.get(loc) {
+        for stmt in prg.stmts.iter() {
+            if let Statement::Use(import) = stmt {
+                if let Ok(dep) = mods.imported(loc, &import.module) {
+                    dependency_order(mods, &dep, order);
+                }
+            }
+        }
+        order.push(loc.clone());
+    }
+}
+
+/// Adds the rules allowed by the annotation of an expression.
+fn allowed<T: Annotated>(outer: &[String], e: &T) -> Vec<String> {
+    let mut allowed = outer.to_vec();
+    if let Some(ann) = e.annotation() {
+        match ann.get_string(LINT_ALLOW) {
+            Some(id) => allowed.push(id),
+            None => allowed.extend(ann.get_enum(LINT_ALLOW).unwrap_or_default()),
+        }
+    }
+    allowed
+}
+
+fn is_pascal_case(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_uppercase()) && s.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn is_kebab_case(s: &str) -> bool {
+    s.split('-').all(|w| {
+        !w.is_empty()
+            && w.chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+    })
+}
+
+fn children<T: AsExpr>(e: &Expr<T>) -> Vec<&T> {
+    match e {
+        Expr::Rel(rel) => rel.into_iter().collect(),
+        Expr::Uri(uri) => uri.into_iter().collect(),
+        Expr::Property(prop) => prop.into_iter().collect(),
+        Expr::Object(obj) => obj.into_iter().collect(),
+        Expr::Content(cnt) => cnt.into_iter().collect(),
+        Expr::Xfer(xfer) => xfer.into_iter().collect(),
+        Expr::Array(array) => array.into_iter().collect(),
+        Expr::Op(operation) => operation.into_iter().collect(),
+        Expr::App(application) => application.into_iter().collect(),
+        Expr::Lambda(_) | Expr::Lit(_) | Expr::Prim(_) | Expr::Var(_) | Expr::Binding(_) => {
+            Vec::new()
+        }
+    }
+}
+
+struct Lint<'a> {
+    linter: &'a Linter,
+    diags: Vec<Diagnostic>,
+    /// The violations already reported, by rule, location and message
+    seen: HashSet<(&'static str, Locator, String, String)>,
+    /// The module being checked, for expressions not knowing their own
+    module: Locator,
+}
+
+impl<'a> Lint<'a> {
+    fn is_checked(&self, rule: &'static str) -> bool {
+        self.linter.levels.get(rule) != Some(&Level::Allow)
+    }
+
+    /// Reports a violation found in an expression, unless allowed.
+    fn report<T>(&mut self, rule: &'static str, allowed: &[String], e: &T, msg: String)
+    where
+        T: AsExpr + Located,
+    {
+        let level = self.linter.levels[rule];
+        let module = e.locator().unwrap_or(&self.module).clone();
+        let span = e.as_node().span;
+        let key = (
+            rule,
+            module.clone(),
+            span.map(|s| s.to_string()).unwrap_or_default(),
+            msg,
+        );
+        if level == Level::Allow || allowed.iter().any(|id| id == rule) {
+            return;
+        }
+        if !self.seen.insert(key.clone()) {
+            return;
+        }
+        self.diags.push(Diagnostic {
+            rule,
+            level,
+            message: key.3,
+            module,
+            span,
+        })
+    }
+
+    /// Checks an expression and its children.
+    ///
+    /// Lambdas are not checked, only the expressions they are applied into.
+    fn walk<T: AsExpr + Annotated + Located>(&mut self, e: &T, outer: &[String]) {
+        let allowed = allowed(outer, e);
+        let node = e.as_node();
+        match node.as_expr() {
+            Expr::Xfer(_) => {
+                let summary = e.annotation().and_then(|a| a.get_str("summary"));
+                if summary.is_none() {
+                    let msg = "operation without a summary".to_owned();
+                    self.report(TRANSFER_SUMMARY, &allowed, e, msg);
+                }
+                if self.is_checked(ERROR_RANGES) {
+                    if let Ok(xfer) = Transfer::try_from(e) {
+                        let has = |range: HttpStatusRange, codes: std::ops::Range<u16>| {
+                            xfer.ranges.keys().any(|(s, _)| match s {
+                                Some(HttpStatus::Range(r)) => *r == range,
+                                Some(HttpStatus::Code(c)) => codes.contains(&c.get()),
+                                None => false,
+                            })
+                        };
+                        let missing: Vec<_> = [
+                            ("4XX", has(HttpStatusRange::ClientError, 400..500)),
+                            ("5XX", has(HttpStatusRange::ServerError, 500..600)),
+                        ]
+                        .into_iter()
+                        .filter_map(|(name, has)| if has { None } else { Some(name) })
+                        .collect();
+                        if !missing.is_empty() {
+                            let msg =
+                                format!("operation without {} responses", missing.join(" or "));
+                            self.report(ERROR_RANGES, &allowed, e, msg);
+                        }
+                    }
+                }
+            }
+            Expr::Uri(uri) => {
+                for s in uri.path.iter() {
+                    if let UriSegment::Literal(l) = s {
+                        if !l.is_empty() && !is_kebab_case(l) {
+                            let msg = format!("path segment {} is not kebab-case", l);
+                            self.report(PATH_KEBAB_CASE, &allowed, e, msg);
+                        }
+                    }
+                }
+            }
+            Expr::Op(op) if op.op == Operator::Any => {
+                let msg = "untyped alternative of schemas".to_owned();
+                self.report(NO_ANY, &allowed, e, msg);
+            }
+            Expr::Lambda(_) => return,
+            _ => {}
+        }
+        for child in children(node.as_expr()) {
+            self.walk(child, &allowed);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::lint::{Level, Linter};
+use crate::tests::compile_main;
+
+fn violations(linter: &Linter, code: &str) -> anyhow::Result<Vec<(&'static str, String)>> {
+    let mods = compile_main(code)?;
+    let diags = linter
+        .lint(&mods)
+        .into_iter()
+        .map(|d| (d.rule, d.message))
+        .collect();
+    anyhow::Ok(diags)
+}
+
+#[test]
+fn lint_rules() -> anyhow::Result<()> {
+    let code = r#"
+        let @order = { 'id (int ~ str) };
+        # summary: "gets an order"
+        let op = get -> @order :: <status=4XX,> :: <status=500,>;
+        res /someOrders ( op, put : @order -> @order );
+    "#;
+
+    assert_eq!(
+        violations(&Linter::new(), code)?,
+        vec![
+            (
+                "schema-pascal-case",
+                "reference name @order is not PascalCase".to_owned()
+            ),
+            ("no-any", "untyped alternative of schemas".to_owned()),
+            (
+                "path-kebab-case",
+                "path segment someOrders is not kebab-case".to_owned()
+            ),
+            ("transfer-summary", "operation without a summary".to_owned()),
+            (
+                "error-ranges",
+                "operation without 4XX or 5XX responses".to_owned()
+            ),
+        ]
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn lint_allow() -> anyhow::Result<()> {
+    let code = r#"
+        # lint-allow: [transfer-summary, error-ranges]
+        let op = get -> {};
+        # lint-allow: path-kebab-case
+        res /someOrders ( op );
+        res /other ( op );
+    "#;
+
+    assert!(violations(&Linter::new(), code)?.is_empty());
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn lint_levels() -> anyhow::Result<()> {
+    let code = r#"
+        res /someOrders ( get -> {} );
+    "#;
+
+    let linter = Linter::new()
+        .with_level("transfer-summary", Level::Allow)?
+        .with_level("error-ranges", Level::Allow)?
+        .with_level("path-kebab-case", Level::Deny)?;
+    let diags = linter.lint(&compile_main(code)?);
+
+    assert_eq!(diags.len(), 1);
+    assert_eq!(diags[0].level, Level::Deny);
+
+    assert!(Linter::new().with_level("unknown", Level::Deny).is_err());
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn lint_allow_once() -> anyhow::Result<()> {
+    let code = r#"
+        let f x = { 'v (x ~ str) };
+        # lint-allow: no-any
+        let @A = f int;
+        let @B = f num;
+    "#;
+
+    assert_eq!(
+        violations(&Linter::new(), code)?,
+        vec![("no-any", "untyped alternative of schemas".to_owned())]
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn lint_applications() -> anyhow::Result<()> {
+    let code = r#"
+        let f x = get -> x;
+        res /a ( f {} );
+        res /b ( f { 'b num } );
+    "#;
+
+    let linter = Linter::new().with_level("error-ranges", Level::Allow)?;
+    let diags = linter.lint(&compile_main(code)?);
+
+    // Each operation is reported where the function is applied, not once for its body.
+    let lines: Vec<_> = diags
+        .iter()
+        .map(|d| (d.rule, d.span.map(|s| s.start.0)))
+        .collect();
+    assert_eq!(
+        lines,
+        vec![("transfer-summary", Some(3)), ("transfer-summary", Some(4))]
+    );
+
+    anyhow::Ok(())
+}
@@ -1,4 +1,6 @@
 use crate::errors::{Error, Result};
+use crate::node::NodeMut;
+use crate::scope::Env;
 use std::fmt::{Display, Formatter};
 use std::path::Path;
 use std::rc::Rc;
@@ -14,6 +16,13 @@ impl Locator {
         let url = self.url.join(path).map(Rc::new)?;
         Ok(Locator { url })
     }
+
+    /// Returns the locator of a directory, against which module paths can be joined.
+    pub fn from_dir(p: &Path) -> Result<Locator> {
+        let path = std::env::current_dir()?.join(p);
+        let url = Url::from_directory_path(path).map(Rc::new)?;
+        Ok(Locator { url })
+    }
 }
 
 impl TryFrom<&str> for Locator {
@@ -40,3 +49,21 @@ impl Display for Locator {
         write!(f, "{}", self.url)
     }
 }
+
+/// Expressions that know the module they are declared in.
+pub trait Located {
+    fn locator(&self) -> Option<&Locator>;
+    fn set_locator(&mut self, l: Locator);
+}
+
+/// Visits an abstract syntax tree to assign the module locator to expressions.
+pub fn locate<T: Located>(
+    loc: &mut Locator,
+    _env: &mut Env<T>,
+    node_ref: NodeMut<T>,
+) -> Result<()> {
+    if let NodeMut::Expr(expr) = node_ref {
+        expr.set_locator(loc.clone());
+    }
+    Ok(())
+}
@@ -10,6 +10,8 @@ use std::collections::HashMap;
 pub struct ModuleSet<T> {
     pub base: Locator,
     programs: HashMap<Locator, Program<T>>,
+    /// The module located for each import, by importing module and path
+    imports: HashMap<(Locator, String), Locator>,
 }
 
 impl<T> ModuleSet<T> {
@@ -17,6 +19,7 @@ impl<T> ModuleSet<T> {
         ModuleSet {
             base,
             programs: Default::default(),
+            imports: Default::default(),
         }
     }
 
@@ -35,6 +38,24 @@ impl<T> ModuleSet<T> {
     pub fn get(&self, l: &Locator) -> Option<&Program<T>> {
         self.programs.get(l)
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Locator, &Program<T>)> {
+        self.programs.iter()
+    }
+
+    /// Records the module located for an import.
+    pub fn import(&mut self, from: &Locator, path: &str, l: Locator) {
+        self.imports.insert((from.clone(), path.to_owned()), l);
+    }
+
+    /// Returns the module imported with the given path, relative to the importing module
+    /// unless located otherwise when loading.
+    pub fn imported(&self, from: &Locator, path: &str) -> Result<Locator, Error> {
+        match self.imports.get(&(from.clone(), path.to_owned())) {
+            Some(l) => Ok(l.clone()),
+            None => from.join(path),
+        }
+    }
 }
 
 pub trait Loader<T, E>: Fn(&Locator) -> Result<Program<T>, E>
@@ -65,6 +86,19 @@ where
 {
 }
 
+/// Locates the module imported with a path from another module.
+pub trait Locate<E>: Fn(&Locator, &str) -> Result<Locator, E>
+where
+    E: From<Error>,
+{
+}
+impl<E, F> Locate<E> for F
+where
+    E: From<Error>,
+    F: Fn(&Locator, &str) -> Result<Locator, E>,
+{
+}
+
 pub fn load<T, E, L, C>(loc: &Locator, loader: L, compiler: C) -> Result<ModuleSet<T>, E>
 where
     T: AsExpr,
@@ -72,29 +106,78 @@ where
     L: Loader<T, E>,
     C: Compiler<T, E>,
 {
-    let mut mods = ModuleSet::new(loc.clone());
-    recurse(&mut mods, vec![loc.clone()], &loader, &compiler)?;
+    let locate = |from: &Locator, path: &str| from.join(path).map_err(E::from);
+    load_with(loc, locate, loader, compiler)
+}
+
+/// Loads a program and its dependencies, locating imported modules with the given function.
+pub fn load_with<T, E, P, L, C>(
+    loc: &Locator,
+    locate: P,
+    loader: L,
+    compiler: C,
+) -> Result<ModuleSet<T>, E>
+where
+    T: AsExpr,
+    E: From<Error>,
+    P: Locate<E>,
+    L: Loader<T, E>,
+    C: Compiler<T, E>,
+{
+    load_all(std::slice::from_ref(loc), locate, loader, compiler)
+}
+
+/// Loads several programs and their dependencies into one module set.
+///
+/// Modules shared by several programs are loaded and compiled once.
+/// The first program is the base of the module set.
+pub fn load_all<T, E, P, L, C>(
+    locs: &[Locator],
+    locate: P,
+    loader: L,
+    compiler: C,
+) -> Result<ModuleSet<T>, E>
+where
+    T: AsExpr,
+    E: From<Error>,
+    P: Locate<E>,
+    L: Loader<T, E>,
+    C: Compiler<T, E>,
+{
+    let base = locs
+        .first()
+        .ok_or_else(|| Error::new(Kind::NotFound, "no program to load"))?;
+    let mut mods = ModuleSet::new(base.clone());
+    for loc in locs {
+        recurse(&mut mods, vec![loc.clone()], &locate, &loader, &compiler)?;
+    }
     Ok(mods)
 }
 
-fn recurse<T, E, L, C>(
+fn recurse<T, E, P, L, C>(
     mods: &mut ModuleSet<T>,
     path: Vec<Locator>,
+    locate: &P,
     loader: &L,
     compiler: &C,
 ) -> Result<(), E>
 where
     T: AsExpr,
     E: From<Error>,
+    P: Locate<E>,
     L: Loader<T, E>,
     C: Compiler<T, E>,
 {
     let base = path.last().unwrap();
+    if mods.get(base).is_some() {
+        return Ok(());
+    }
     let prg = loader(base)?;
     let mut deps = Vec::new();
     prg.scan(&mut deps, &mut Env::new(None), &mut dependency_scan)?;
     deps.into_iter().try_for_each(|dep| {
-        let module = base.join(dep.as_str())?;
+        let module = locate(base, dep.as_str())?;
+        mods.import(base, dep.as_str(), module.clone());
         if path.contains(&module) {
             Err(Error::new(Kind::CycleDetected, "loading module")
                 .with(&base)
@@ -103,7 +186,7 @@ where
         } else {
             let mut next = path.clone();
             next.push(module);
-            recurse(mods, next, loader, compiler)
+            recurse(mods, next, locate, loader, compiler)
         }
     })?;
     let prog = compiler(mods, base, prg)?;
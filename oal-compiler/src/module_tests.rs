@@ -1,8 +1,10 @@
 use crate::errors::{Kind, Result};
 use crate::locator::Locator;
-use crate::module::load;
+use crate::module::{load, load_all};
+use crate::spec::Spec;
 use crate::{ModuleSet, Program};
 use oal_syntax::parse;
+use std::cell::RefCell;
 
 #[test]
 fn module_simple() {
@@ -33,3 +35,36 @@ fn module_cycle() {
         Kind::CycleDetected
     );
 }
+
+#[test]
+fn module_entries() -> anyhow::Result<()> {
+    let a = &Locator::try_from("test:/a.oal")?;
+    let b = &Locator::try_from("test:/b.oal")?;
+    let common = &Locator::try_from("test:/common.oal")?;
+    let loaded = RefCell::new(Vec::new());
+    let loader = |l: &Locator| -> Result<Program> {
+        loaded.borrow_mut().push(l.clone());
+        let code = if l == common {
+            "let @err = {};"
+        } else if l == a {
+            r#"use "common.oal"; res /a ( get -> @err );"#
+        } else {
+            r#"use "common.oal"; res /b ( get -> @err );"#
+        };
+        Ok(parse(code)?)
+    };
+    let locate = |from: &Locator, path: &str| from.join(path);
+
+    let mods = load_all(&[a.clone(), b.clone()], locate, loader, crate::compile)?;
+
+    assert_eq!(mods.len(), 3);
+    assert_eq!(loaded.borrow().iter().filter(|l| *l == common).count(), 1);
+
+    let spec_a = Spec::from_entry(&mods, a)?;
+    let spec_b = Spec::from_entry(&mods, b)?;
+    assert_eq!(spec_a.rels.keys().collect::<Vec<_>>(), vec!["/a"]);
+    assert_eq!(spec_b.rels.keys().collect::<Vec<_>>(), vec!["/b"]);
+    assert_eq!(spec_b.origins.values().next().map(|s| &s.loc), Some(common));
+
+    anyhow::Ok(())
+}
@@ -1,4 +1,5 @@
 use crate::errors::{Error, Kind, Result};
+use crate::locator::Located;
 use crate::node::NodeMut;
 use crate::scope::Env;
 use crate::transform::Transform;
@@ -14,9 +15,10 @@ pub trait Semigroup: Sized {
 /// Visits an abstract syntax tree to reduce expressions.
 pub fn reduce<T>(_acc: &mut (), env: &mut Env<T>, node_ref: NodeMut<T>) -> Result<()>
 where
-    T: AsExpr + Semigroup,
+    T: AsExpr + Semigroup + Located,
 {
     if let NodeMut::Expr(expr) = node_ref {
+        let loc = expr.locator().cloned();
         let node = expr.as_node_mut();
         let span = node.span;
         match node.as_expr_mut() {
@@ -47,6 +49,13 @@ where
                             .as_expr_mut()
                             .transform(&mut (), app_env, &mut reduce)?;
                         expr.combine(app);
+                        // The result of an application is located where the function is applied,
+                        // so that errors about the result point to the arguments given there
+                        // rather than to the body shared by all applications.
+                        expr.as_node_mut().span = span;
+                        if let Some(loc) = loc {
+                            expr.set_locator(loc);
+                        }
                         Ok(())
                     } else {
                         Err(Error::new(Kind::NotAFunction, "").with(expr))
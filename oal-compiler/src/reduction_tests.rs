@@ -113,3 +113,41 @@ fn reduce_reference() {
         _ => panic!("expected declaration"),
     }
 }
+
+#[test]
+fn reduce_application_span() {
+    let code = r#"
+        let f x = { 'a x };
+        let b = f num;
+    "#;
+    let parsed: Program = parse(code).expect("parsing failed");
+    let call = match parsed.stmts.get(1) {
+        Some(Statement::Decl(d)) => d.expr.as_node().span,
+        _ => panic!("expected declaration"),
+    };
+    let body = match parsed.stmts.first() {
+        Some(Statement::Decl(d)) => match d.expr.as_node().as_expr() {
+            Expr::Lambda(l) => l.body.as_node().span,
+            _ => panic!("expected lambda"),
+        },
+        _ => panic!("expected declaration"),
+    };
+    let prg = eval(code).expect("evaluation failed");
+
+    match prg.stmts.get(1).unwrap() {
+        Statement::Decl(d) => {
+            assert!(call.is_some());
+            assert_ne!(call, body);
+            assert_eq!(d.expr.as_node().span, call);
+            match d.expr.as_node().as_expr() {
+                Expr::Object(o) => {
+                    // The inner nodes of the result are still located in the body of the function.
+                    let prop = o.props.first().unwrap().as_node().span.unwrap();
+                    assert!(prop.start >= body.unwrap().start && prop.end <= body.unwrap().end);
+                }
+                _ => panic!("expected object"),
+            }
+        }
+        _ => panic!("expected declaration"),
+    }
+}
@@ -0,0 +1,185 @@
+use crate::errors::{Error, Kind, Result};
+use crate::locator::Locator;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// A provider of module sources for a URL scheme.
+pub trait Resolver {
+    fn resolve(&self, loc: &Locator) -> Result<String>;
+
+    /// Tells whether a module exists at the given location.
+    fn exists(&self, loc: &Locator) -> bool {
+        self.resolve(loc).is_ok()
+    }
+}
+
+impl<F> Resolver for F
+where
+    F: Fn(&Locator) -> Result<String>,
+{
+    fn resolve(&self, loc: &Locator) -> Result<String> {
+        self(loc)
+    }
+}
+
+/// Resolves `file:` locators from the file system.
+#[derive(Clone, Debug, Default)]
+pub struct FileResolver;
+
+impl Resolver for FileResolver {
+    fn resolve(&self, loc: &Locator) -> Result<String> {
+        let path = loc
+            .url
+            .to_file_path()
+            .map_err(|_| Error::new(Kind::InvalidURL, "not a file path").with(&loc.to_string()))?;
+        let source = std::fs::read_to_string(path)?;
+        Ok(source)
+    }
+
+    fn exists(&self, loc: &Locator) -> bool {
+        matches!(loc.url.to_file_path(), Ok(p) if p.is_file())
+    }
+}
+
+/// Resolves locators from sources held in memory, typically for the `mem:` scheme.
+#[derive(Clone, Debug, Default)]
+pub struct MemResolver {
+    sources: HashMap<Locator, String>,
+}
+
+impl MemResolver {
+    pub fn new() -> Self {
+        MemResolver::default()
+    }
+
+    pub fn insert<S: Into<String>>(&mut self, loc: Locator, source: S) {
+        self.sources.insert(loc, source.into());
+    }
+
+    pub fn with_source<S: Into<String>>(mut self, loc: Locator, source: S) -> Self {
+        self.insert(loc, source);
+        self
+    }
+}
+
+impl Resolver for MemResolver {
+    fn resolve(&self, loc: &Locator) -> Result<String> {
+        self.sources
+            .get(loc)
+            .cloned()
+            .ok_or_else(|| Error::new(Kind::NotFound, "no such module").with(&loc.to_string()))
+    }
+}
+
+/// Resolves locators as paths relative to a local directory, whatever the scheme.
+///
+/// With `registry:/acme/errors.oal` and a root directory `/srv/registry`,
+/// the source is read from `/srv/registry/acme/errors.oal`.
+#[derive(Clone, Debug)]
+pub struct DirResolver {
+    root: PathBuf,
+}
+
+impl DirResolver {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        DirResolver { root: root.into() }
+    }
+}
+
+impl DirResolver {
+    fn path(&self, loc: &Locator) -> Result<PathBuf> {
+        let rel = Path::new(loc.url.path().trim_start_matches('/'));
+        if rel.components().all(|c| matches!(c, Component::Normal(_))) {
+            Ok(self.root.join(rel))
+        } else {
+            Err(Error::new(Kind::InvalidURL, "not a relative path").with(&loc.to_string()))
+        }
+    }
+}
+
+impl Resolver for DirResolver {
+    fn resolve(&self, loc: &Locator) -> Result<String> {
+        let source = std::fs::read_to_string(self.path(loc)?)?;
+        Ok(source)
+    }
+
+    fn exists(&self, loc: &Locator) -> bool {
+        matches!(self.path(loc), Ok(p) if p.is_file())
+    }
+}
+
+/// A registry of resolvers keyed by URL scheme.
+///
+/// The `file:` scheme is registered by default.
+pub struct Resolvers {
+    schemes: HashMap<String, Box<dyn Resolver>>,
+}
+
+impl Default for Resolvers {
+    fn default() -> Self {
+        Resolvers::new().with("file", FileResolver)
+    }
+}
+
+impl Resolvers {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Resolvers {
+            schemes: HashMap::new(),
+        }
+    }
+
+    /// Registers the resolver for a scheme, replacing any previous one.
+    pub fn register<R: Resolver + 'static>(&mut self, scheme: &str, resolver: R) {
+        self.schemes.insert(scheme.to_owned(), Box::new(resolver));
+    }
+
+    pub fn with<R: Resolver + 'static>(mut self, scheme: &str, resolver: R) -> Self {
+        self.register(scheme, resolver);
+        self
+    }
+
+    /// Returns the source of a module with the resolver registered for its scheme.
+    pub fn resolve(&self, loc: &Locator) -> Result<String> {
+        match self.schemes.get(loc.url.scheme()) {
+            Some(r) => r.resolve(loc),
+            None => {
+                Err(Error::new(Kind::UnknownScheme, "no resolver for scheme")
+                    .with(&loc.to_string()))
+            }
+        }
+    }
+
+    pub fn exists(&self, loc: &Locator) -> bool {
+        matches!(self.schemes.get(loc.url.scheme()), Some(r) if r.exists(loc))
+    }
+
+    /// Locates the module imported with a path from another module.
+    ///
+    /// The path is first resolved relative to the importing module.
+    /// Bare paths, i.e. neither absolute URLs nor starting with `/`, `./` or `../`,
+    /// are then looked up in each directory of the search path, in order.
+    pub fn locate(&self, from: &Locator, path: &str, search: &[Locator]) -> Result<Locator> {
+        let relative = from.join(path);
+        let bare = is_bare(path);
+        if !bare {
+            return relative;
+        }
+        let candidates: Vec<Locator> = relative
+            .into_iter()
+            .chain(search.iter().filter_map(|dir| dir.join(path).ok()))
+            .collect();
+        match candidates.iter().find(|l| self.exists(l)) {
+            Some(l) => Ok(l.clone()),
+            None => Err(candidates.iter().fold(
+                Error::new(Kind::NotFound, format!("module not found: {}", path)),
+                |err, l| err.with(&format!("tried {}", l)),
+            )),
+        }
+    }
+}
+
+/// Tells whether an import path is to be looked up in the search path.
+fn is_bare(path: &str) -> bool {
+    url::Url::parse(path).is_err() && !["/", "./", "../"].iter().any(|p| path.starts_with(p))
+}
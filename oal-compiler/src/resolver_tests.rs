@@ -0,0 +1,108 @@
+use crate::compile::compile;
+use crate::errors::{Kind, Result};
+use crate::locator::Locator;
+use crate::module::{load, load_with};
+use crate::resolver::{DirResolver, MemResolver, Resolvers};
+use crate::{ModuleSet, Program};
+use oal_syntax::parse;
+
+fn compiler(_mods: &ModuleSet, _l: &Locator, p: Program) -> Result<Program> {
+    Ok(p)
+}
+
+#[test]
+fn resolver_mem() -> anyhow::Result<()> {
+    let main = &Locator::try_from("mem:/main.oal")?;
+    let module = &Locator::try_from("mem:/common.oal")?;
+    let mem = MemResolver::new()
+        .with_source(main.clone(), r#"use "common.oal";"#)
+        .with_source(module.clone(), "let id = num;");
+    let resolvers = Resolvers::new().with("mem", mem);
+    let loader = |l: &Locator| -> Result<Program> { Ok(parse(resolvers.resolve(l)?)?) };
+
+    let mods = load(main, loader, compiler)?;
+
+    assert_eq!(mods.len(), 2);
+    assert!(mods.get(module).is_some());
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn resolver_custom_scheme() -> anyhow::Result<()> {
+    let root = std::env::temp_dir().join(format!("oal-registry-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("acme"))?;
+    std::fs::write(root.join("acme/errors.oal"), "let @error = {};")?;
+
+    let main = &Locator::try_from("mem:/main.oal")?;
+    let module = &Locator::try_from("registry:/acme/errors.oal")?;
+    let mem = MemResolver::new().with_source(main.clone(), r#"use "registry:/acme/errors.oal";"#);
+    let resolvers = Resolvers::default()
+        .with("mem", mem)
+        .with("registry", DirResolver::new(&root));
+    let loader = |l: &Locator| -> Result<Program> { Ok(parse(resolvers.resolve(l)?)?) };
+
+    let mods = load(main, loader, compiler);
+
+    std::fs::remove_dir_all(&root)?;
+
+    assert!(mods?.get(module).is_some());
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn resolver_unknown_scheme() -> anyhow::Result<()> {
+    let loc = &Locator::try_from("registry:/acme/errors.oal")?;
+
+    let err = Resolvers::default()
+        .resolve(loc)
+        .expect_err("expected unknown scheme");
+
+    assert_eq!(err.kind, Kind::UnknownScheme);
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn resolver_search_path() -> anyhow::Result<()> {
+    let main = &Locator::try_from("mem:/app/main.oal")?;
+    let lib = &Locator::try_from("mem:/lib/")?;
+    let module = &Locator::try_from("mem:/lib/acme/errors.oal")?;
+    let mem = MemResolver::new()
+        .with_source(main.clone(), r#"use "acme/errors.oal"; let @a = @errors;"#)
+        .with_source(
+            module.clone(),
+            r#"use "common.oal"; let @errors = [@error];"#,
+        )
+        .with_source(lib.join("acme/common.oal")?, "let @error = {};");
+    let resolvers = Resolvers::new().with("mem", mem);
+    let search = vec![lib.clone()];
+    let locate = |from: &Locator, path: &str| resolvers.locate(from, path, &search);
+    let loader = |l: &Locator| -> Result<Program> { Ok(parse(resolvers.resolve(l)?)?) };
+
+    let mods = load_with(main, locate, loader, compile)?;
+
+    assert_eq!(mods.len(), 3);
+    assert_eq!(mods.imported(main, "acme/errors.oal")?, *module);
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn resolver_search_path_not_found() -> anyhow::Result<()> {
+    let main = &Locator::try_from("mem:/app/main.oal")?;
+    let search = vec![Locator::try_from("mem:/lib/")?];
+
+    let err = Resolvers::new()
+        .with("mem", MemResolver::new())
+        .locate(main, "acme/errors.oal", &search)
+        .expect_err("expected module not found");
+
+    assert_eq!(err.kind, Kind::NotFound);
+    let msg = err.to_string();
+    assert!(msg.contains("mem:/app/acme/errors.oal"));
+    assert!(msg.contains("mem:/lib/acme/errors.oal"));
+
+    anyhow::Ok(())
+}
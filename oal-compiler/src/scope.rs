@@ -1,4 +1,5 @@
 use crate::errors::Result;
+use crate::locator::Locator;
 use crate::module::ModuleSet;
 use crate::node::NodeRef;
 use crate::scan::Scan;
@@ -11,6 +12,8 @@ pub type Scope<T> = HashMap<Ident, T>;
 pub struct Env<'a, T> {
     scopes: Vec<Scope<T>>,
     modules: Option<&'a ModuleSet<T>>,
+    /// The module being processed, if not the main one
+    module: Option<&'a Locator>,
 }
 
 impl<'a, T> Env<'a, T>
@@ -21,9 +24,16 @@ where
         Env {
             scopes: vec![Scope::new()],
             modules: mods,
+            module: None,
         }
     }
 
+    /// Sets the module from which imports are resolved.
+    pub fn within_module(mut self, loc: &'a Locator) -> Self {
+        self.module = Some(loc);
+        self
+    }
+
     #[cfg(test)]
     pub fn head(&self) -> &Scope<T> {
         self.scopes.last().unwrap()
@@ -60,7 +70,7 @@ where
 
     pub fn import(&mut self, path: &str) -> Result<()> {
         if let Some(mods) = self.modules {
-            let loc = mods.base.join(path)?;
+            let loc = mods.imported(self.module.unwrap_or(&mods.base), path)?;
             if let Some(m) = mods.get(&loc) {
                 m.scan(self, &mut Env::new(None), &mut declaration_scan)
             } else {
@@ -1,5 +1,6 @@
 use crate::annotation::Annotated;
 use crate::errors::{Error, Kind, Result};
+use crate::locator::{Located, Locator};
 use crate::module::ModuleSet;
 use crate::node::NodeRef;
 use crate::scan::Scan;
@@ -8,8 +9,35 @@ use enum_map::EnumMap;
 use indexmap::IndexMap;
 use oal_syntax::ast::AsExpr;
 use oal_syntax::atom::{HttpStatus, Ident, Text};
+use oal_syntax::span::Span;
 use oal_syntax::{ast, atom};
-use std::fmt::Debug;
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter};
+
+/// The location of the expression a part of the specification is exported from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Source {
+    pub loc: Locator,
+    pub span: Option<Span>,
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.span {
+            Some(span) => write!(f, "{} {}", self.loc, span),
+            None => write!(f, "{}", self.loc),
+        }
+    }
+}
+
+impl Source {
+    fn of<T: AsExpr + Located>(e: &T) -> Option<Self> {
+        e.locator().map(|loc| Source {
+            loc: loc.clone(),
+            span: e.as_node().span,
+        })
+    }
+}
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum UriSegment {
@@ -19,7 +47,7 @@ pub enum UriSegment {
 
 impl<T> TryFrom<&ast::UriSegment<T>> for UriSegment
 where
-    T: AsExpr + Annotated,
+    T: AsExpr + Annotated + Located,
 {
     type Error = Error;
 
@@ -51,7 +79,7 @@ impl Uri {
             .collect()
     }
 
-    fn try_from<T: AsExpr + Annotated>(e: &T) -> Result<Self> {
+    fn try_from<T: AsExpr + Annotated + Located>(e: &T) -> Result<Self> {
         if let ast::Expr::Uri(uri) = e.as_node().as_expr() {
             let path = uri
                 .path
@@ -83,7 +111,7 @@ pub struct Array {
 }
 
 impl Array {
-    fn try_from<T: AsExpr + Annotated>(e: &T) -> Result<Self> {
+    fn try_from<T: AsExpr + Annotated + Located>(e: &T) -> Result<Self> {
         if let ast::Expr::Array(a) = e.as_node().as_expr() {
             Schema::try_from(a.item.as_ref()).map(|item| Array { item })
         } else {
@@ -99,7 +127,7 @@ pub struct VariadicOp {
 }
 
 impl VariadicOp {
-    fn try_from<T: AsExpr + Annotated>(e: &T) -> Result<Self> {
+    fn try_from<T: AsExpr + Annotated + Located>(e: &T) -> Result<Self> {
         if let ast::Expr::Op(op) = e.as_node().as_expr() {
             let schemas: Result<Vec<_>> = op.exprs.iter().map(Schema::try_from).collect();
             schemas.map(|schemas| VariadicOp { op: op.op, schemas })
@@ -118,7 +146,7 @@ pub struct Schema {
 }
 
 impl Schema {
-    fn try_from<T: AsExpr + Annotated>(e: &T) -> Result<Self> {
+    fn try_from<T: AsExpr + Annotated + Located>(e: &T) -> Result<Self> {
         let expr = SchemaExpr::try_from(e)?;
         let ann = e.annotation();
         let desc = ann.and_then(|a| a.get_string("description"));
@@ -142,7 +170,7 @@ pub struct PrimNumber {
 }
 
 impl PrimNumber {
-    fn try_from<T: AsExpr + Annotated>(e: &T) -> Result<Self> {
+    fn try_from<T: AsExpr + Annotated + Located>(e: &T) -> Result<Self> {
         let ann = e.annotation();
         let minimum = ann.and_then(|a| a.get_num("minimum"));
         let maximum = ann.and_then(|a| a.get_num("maximum"));
@@ -165,7 +193,7 @@ pub struct PrimString {
 }
 
 impl PrimString {
-    fn try_from<T: AsExpr + Annotated>(e: &T) -> Result<Self> {
+    fn try_from<T: AsExpr + Annotated + Located>(e: &T) -> Result<Self> {
         let ann = e.annotation();
         let pattern = ann.and_then(|a| a.get_string("pattern"));
         let enumeration = ann.and_then(|a| a.get_enum("enum")).unwrap_or_default();
@@ -182,7 +210,7 @@ impl PrimString {
 pub struct PrimBoolean {}
 
 impl PrimBoolean {
-    fn try_from<T: AsExpr + Annotated>(_: &T) -> Result<Self> {
+    fn try_from<T: AsExpr + Annotated + Located>(_: &T) -> Result<Self> {
         Ok(PrimBoolean {})
     }
 }
@@ -196,7 +224,7 @@ pub struct PrimInteger {
 }
 
 impl PrimInteger {
-    fn try_from<T: AsExpr + Annotated>(e: &T) -> Result<Self> {
+    fn try_from<T: AsExpr + Annotated + Located>(e: &T) -> Result<Self> {
         let ann = e.annotation();
         let minimum = ann.and_then(|a| a.get_int("minimum"));
         let maximum = ann.and_then(|a| a.get_int("maximum"));
@@ -226,7 +254,7 @@ pub enum SchemaExpr {
 }
 
 impl SchemaExpr {
-    fn try_from<T: AsExpr + Annotated>(e: &T) -> Result<Self> {
+    fn try_from<T: AsExpr + Annotated + Located>(e: &T) -> Result<Self> {
         let node = e.as_node();
         let span = node.span;
         match node.as_expr() {
@@ -265,7 +293,7 @@ pub struct Property {
 }
 
 impl Property {
-    fn try_from<T: AsExpr + Annotated>(e: &T) -> Result<Self> {
+    fn try_from<T: AsExpr + Annotated + Located>(e: &T) -> Result<Self> {
         if let ast::Expr::Property(prop) = e.as_node().as_expr() {
             let name = prop.name.clone();
             let schema = Schema::try_from(prop.val.as_ref())?;
@@ -290,7 +318,7 @@ pub struct Object {
 }
 
 impl Object {
-    fn try_from<T: AsExpr + Annotated>(e: &T) -> Result<Self> {
+    fn try_from<T: AsExpr + Annotated + Located>(e: &T) -> Result<Self> {
         if let ast::Expr::Object(o) = e.as_node().as_expr() {
             let props: Result<Vec<_>> = o.props.iter().map(Property::try_from).collect();
             props.map(|props| Object { props })
@@ -300,7 +328,7 @@ impl Object {
     }
 }
 
-fn try_into_status<T: AsExpr + Annotated>(e: &T) -> Result<HttpStatus> {
+fn try_into_status<T: AsExpr + Annotated + Located>(e: &T) -> Result<HttpStatus> {
     match e.as_node().as_expr() {
         ast::Expr::Lit(ast::Literal::Status(s)) => Ok(*s),
         ast::Expr::Lit(ast::Literal::Number(n)) => {
@@ -311,7 +339,7 @@ fn try_into_status<T: AsExpr + Annotated>(e: &T) -> Result<HttpStatus> {
     }
 }
 
-fn try_into_media<T: AsExpr + Annotated>(e: &T) -> Result<MediaType> {
+fn try_into_media<T: AsExpr + Annotated + Located>(e: &T) -> Result<MediaType> {
     match e.as_node().as_expr() {
         ast::Expr::Lit(ast::Literal::Text(t)) => Ok(t.as_ref().to_owned()),
         _ => Err(Error::new(Kind::UnexpectedExpression, "not a media expression").with(e)),
@@ -327,6 +355,7 @@ pub struct Content {
     pub media: Option<MediaType>,
     pub headers: Option<Object>,
     pub desc: Option<String>,
+    pub source: Option<Source>,
 }
 
 impl From<Schema> for Content {
@@ -342,12 +371,13 @@ impl From<Schema> for Content {
             media,
             headers,
             desc,
+            source: None,
         }
     }
 }
 
 impl Content {
-    fn try_from<T: AsExpr + Annotated>(e: &T) -> Result<Self> {
+    fn try_from<T: AsExpr + Annotated + Located>(e: &T) -> Result<Self> {
         if let ast::Expr::Content(content) = e.as_node().as_expr() {
             let schema = match &content.schema {
                 Some(s) => Schema::try_from(s.as_ref()).map(|s| Some(Box::new(s))),
@@ -372,22 +402,28 @@ impl Content {
                 .as_ref()
                 .map_or(Ok(None), |h| Object::try_from(h.as_ref()).map(Some))?;
             let desc = e.annotation().and_then(|a| a.get_string("description"));
+            let source = Source::of(e);
             Ok(Content {
                 schema,
                 status,
                 media,
                 headers,
                 desc,
+                source,
             })
         } else {
-            Schema::try_from(e).map(Content::from)
+            let source = Source::of(e);
+            Schema::try_from(e).map(|s| Content {
+                source,
+                ..Content::from(s)
+            })
         }
     }
 }
 
 pub type Ranges = IndexMap<(Option<HttpStatus>, Option<MediaType>), Content>;
 
-fn try_into_ranges<T: AsExpr + Annotated>(ranges: &mut Ranges, e: &T) -> Result<()> {
+fn try_into_ranges<T: AsExpr + Annotated + Located>(ranges: &mut Ranges, e: &T) -> Result<()> {
     match e.as_node().as_expr() {
         ast::Expr::Op(op) if op.op == ast::Operator::Range => {
             op.exprs.iter().try_for_each(|r| try_into_ranges(ranges, r))
@@ -410,10 +446,11 @@ pub struct Transfer {
     pub summary: Option<String>,
     pub tags: Vec<String>,
     pub id: Option<String>,
+    pub source: Option<Source>,
 }
 
 impl Transfer {
-    fn try_from<T: AsExpr + Annotated>(e: &T) -> Result<Self> {
+    pub(crate) fn try_from<T: AsExpr + Annotated + Located>(e: &T) -> Result<Self> {
         if let ast::Expr::Xfer(xfer) = e.as_node().as_expr() {
             let methods = xfer.methods;
             let mut ranges = IndexMap::new();
@@ -431,6 +468,7 @@ impl Transfer {
             let summary = ann.and_then(|a| a.get_string("summary"));
             let tags = ann.and_then(|a| a.get_enum("tags")).unwrap_or_default();
             let id = ann.and_then(|a| a.get_string("operationId"));
+            let source = Source::of(e);
             Ok(Transfer {
                 methods,
                 domain,
@@ -440,6 +478,7 @@ impl Transfer {
                 summary,
                 tags,
                 id,
+                source,
             })
         } else {
             Err(Error::new(Kind::UnexpectedExpression, "not a transfer").with(e))
@@ -453,10 +492,11 @@ pub type Transfers = EnumMap<atom::Method, Option<Transfer>>;
 pub struct Relation {
     pub uri: Uri,
     pub xfers: Transfers,
+    pub source: Option<Source>,
 }
 
 impl Relation {
-    fn try_from<T: AsExpr + Annotated>(e: &T) -> Result<Self> {
+    fn try_from<T: AsExpr + Annotated + Located>(e: &T) -> Result<Self> {
         if let ast::Expr::Rel(rel) = e.as_node().as_expr() {
             let uri = Uri::try_from(rel.uri.as_ref())?;
             let mut xfers = Transfers::default();
@@ -468,7 +508,8 @@ impl Relation {
                     }
                 }
             }
-            Ok(Relation { uri, xfers })
+            let source = Source::of(e);
+            Ok(Relation { uri, xfers, source })
         } else {
             Err(Error::new(Kind::UnexpectedExpression, "not a relation").with(e))
         }
@@ -481,40 +522,149 @@ pub enum Reference {
 }
 
 impl Reference {
-    fn try_from<T: AsExpr + Annotated>(e: &T) -> Result<Self> {
+    fn try_from<T: AsExpr + Annotated + Located>(e: &T) -> Result<Self> {
         let s = Schema::try_from(e)?;
         Ok(Reference::Schema(s))
     }
 }
 
+/// The specification of a single expression, outside of any resource.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Fragment {
+    Schema(Schema),
+    Property(Property),
+    Ranges(Ranges),
+    Transfer(Box<Transfer>),
+    Relation(Box<Relation>),
+}
+
+impl Fragment {
+    fn try_from<T: AsExpr + Annotated + Located>(e: &T) -> Result<Self> {
+        match e.as_node().as_expr() {
+            ast::Expr::Rel(_) => Relation::try_from(e).map(|r| Fragment::Relation(Box::new(r))),
+            ast::Expr::Xfer(_) => Transfer::try_from(e).map(|x| Fragment::Transfer(Box::new(x))),
+            ast::Expr::Property(_) => Property::try_from(e).map(Fragment::Property),
+            ast::Expr::Content(_) => {
+                let mut ranges = Ranges::new();
+                try_into_ranges(&mut ranges, e).map(|_| Fragment::Ranges(ranges))
+            }
+            ast::Expr::Op(op) if op.op == ast::Operator::Range => {
+                let mut ranges = Ranges::new();
+                try_into_ranges(&mut ranges, e).map(|_| Fragment::Ranges(ranges))
+            }
+            _ => Schema::try_from(e).map(Fragment::Schema),
+        }
+    }
+}
+
 pub type PathPattern = String;
 pub type Relations = IndexMap<PathPattern, Relation>;
 pub type References = IndexMap<Ident, Reference>;
+pub type Origins = IndexMap<Ident, Source>;
 
 #[derive(Clone, Debug, PartialEq, Default)]
 pub struct Spec {
     pub rels: Relations,
     pub refs: References,
+    /// The declaration of each reference, in the module declaring it
+    pub origins: Origins,
 }
 
 impl<T> TryFrom<&ModuleSet<T>> for Spec
 where
-    T: AsExpr + Annotated,
+    T: AsExpr + Annotated + Located,
 {
     type Error = Error;
 
     fn try_from(mods: &ModuleSet<T>) -> Result<Self> {
+        Spec::from_entry(mods, &mods.base)
+    }
+}
+
+impl Spec {
+    /// Exports the specification of one of the programs of a module set.
+    pub fn from_entry<T>(mods: &ModuleSet<T>, main: &Locator) -> Result<Self>
+    where
+        T: AsExpr + Annotated + Located,
+    {
+        let prg = mods
+            .get(main)
+            .ok_or_else(|| Error::new(Kind::NotFound, "no such program").with(main))?;
         let mut spec = Spec::default();
-        let prg = mods.main();
-        prg.scan(&mut spec, &mut Env::new(Some(mods)), &mut export)?;
+        let env = &mut Env::new(Some(mods)).within_module(main);
+        prg.scan(&mut spec, env, &mut export)?;
+        spec.origins = origins(mods, main, &spec.refs);
         Ok(spec)
     }
 }
 
+impl Spec {
+    /// Exports the specification of a declaration of a module, along with the references it uses.
+    ///
+    /// A declaration standing for a reference is exported as the referenced schema.
+    pub fn from_decl<T>(
+        mods: &ModuleSet<T>,
+        loc: &Locator,
+        name: &Ident,
+    ) -> Result<(Fragment, Self)>
+    where
+        T: AsExpr + Annotated + Located,
+    {
+        let prg = mods
+            .get(loc)
+            .ok_or_else(|| Error::new(Kind::NotFound, "no such program").with(loc))?;
+        let env = &mut Env::new(Some(mods)).within_module(loc);
+        prg.stmts
+            .iter()
+            .filter(|s| matches!(s, ast::Statement::Decl(_) | ast::Statement::Use(_)))
+            .try_for_each(|s| s.scan(&mut (), env, &mut |_, _, _| Ok::<_, Error>(())))?;
+        let mut expr = env
+            .lookup(name)
+            .ok_or_else(|| Error::new(Kind::NotInScope, "no such declaration").with(name))?;
+        while let ast::Expr::Var(var) = expr.as_node().as_expr() {
+            match env.lookup(var) {
+                Some(val) if var.is_reference() => expr = val,
+                _ => break,
+            }
+        }
+        let expr = expr.clone();
+        let mut spec = Spec::default();
+        expr.as_node().as_expr().scan(&mut spec, env, &mut export)?;
+        spec.origins = origins(mods, loc, &spec.refs);
+        let fragment = Fragment::try_from(&expr)?;
+        Ok((fragment, spec))
+    }
+}
+
+/// Resolves the module declaring each of the given references.
+///
+/// Declarations from the main module take precedence over imported ones,
+/// which take precedence over each other in the order of their URL, for stable outputs.
+fn origins<T: AsExpr>(mods: &ModuleSet<T>, main: &Locator, refs: &References) -> Origins {
+    let mut decls = HashMap::new();
+    let (main, mut imported): (Vec<_>, Vec<_>) = mods.iter().partition(|(l, _)| *l == main);
+    imported.sort_by(|(a, _), (b, _)| a.url.as_str().cmp(b.url.as_str()));
+    for (loc, prg) in main.into_iter().chain(imported) {
+        for stmt in prg.stmts.iter() {
+            if let ast::Statement::Decl(d) = stmt {
+                if d.name.is_reference() {
+                    decls.entry(d.name.clone()).or_insert(Source {
+                        loc: loc.clone(),
+                        span: d.expr.as_node().span,
+                    });
+                }
+            }
+        }
+    }
+    refs.keys()
+        .filter_map(|name| decls.get(name).map(|s| (name.clone(), s.clone())))
+        .collect()
+}
+
 /// Visits an abstract syntax tree to export references and relations.
 fn export<T>(spec: &mut Spec, env: &mut Env<T>, node_ref: NodeRef<T>) -> Result<()>
 where
-    T: AsExpr + Annotated,
+    T: AsExpr + Annotated + Located,
 {
     match node_ref {
         NodeRef::Expr(expr) => {
@@ -523,11 +673,14 @@ where
             match node.as_expr() {
                 ast::Expr::Var(name) if name.is_reference() => match env.lookup(name) {
                     None => Err(Error::new(Kind::NotInScope, "").with(expr)),
-                    Some(val) => {
-                        let ref_ = Reference::try_from(val)?;
-                        spec.refs.entry(name.clone()).or_insert(ref_);
-                        Ok(())
+                    Some(val) if !spec.refs.contains_key(name) => {
+                        let val = val.clone();
+                        let ref_ = Reference::try_from(&val)?;
+                        spec.refs.insert(name.clone(), ref_);
+                        // The referenced value may itself use references declared elsewhere.
+                        val.as_node().as_expr().scan(spec, env, &mut export)
                     }
+                    Some(_) => Ok(()),
                 },
                 _ => Ok(()),
             }
@@ -1,7 +1,8 @@
 use crate::compile::compile;
 use crate::errors::{Error, Kind};
 use crate::spec::{Content, Object, Reference, SchemaExpr, Spec, Uri, UriSegment};
-use crate::{Locator, ModuleSet, Program};
+use crate::{load, Locator, ModuleSet, Program};
+use oal_syntax::atom::Ident;
 use oal_syntax::{atom, parse};
 
 fn eval(code: &str) -> anyhow::Result<Spec> {
@@ -157,3 +158,64 @@ fn evaluate_reference() -> anyhow::Result<()> {
 
     anyhow::Ok(())
 }
+
+#[test]
+fn evaluate_origins() -> anyhow::Result<()> {
+    let module = &Locator::try_from("test:module.oal")?;
+    let main = &Locator::try_from("test:main.oal")?;
+    let loader = |m: &Locator| -> crate::Result<Program> {
+        if m == module {
+            Ok(parse("let @a = {};")?)
+        } else if m == main {
+            Ok(parse(
+                r#"use "test:module.oal"; let @b = { 'a @a }; res / ( get -> @b );"#,
+            )?)
+        } else {
+            unreachable!()
+        }
+    };
+    let mods = load(main, loader, compile)?;
+
+    let spec = Spec::try_from(&mods)?;
+
+    assert_eq!(spec.refs.len(), 2);
+    assert_eq!(
+        spec.origins.get(&Ident::from("@a")).map(|s| &s.loc),
+        Some(module)
+    );
+    assert_eq!(
+        spec.origins.get(&Ident::from("@b")).map(|s| &s.loc),
+        Some(main)
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn evaluate_origins_stable() -> anyhow::Result<()> {
+    let first = &Locator::try_from("test:first.oal")?;
+    let second = &Locator::try_from("test:second.oal")?;
+    let main = &Locator::try_from("test:main.oal")?;
+    let loader = |m: &Locator| -> crate::Result<Program> {
+        if m == first || m == second {
+            Ok(parse("let @a = {};")?)
+        } else if m == main {
+            Ok(parse(
+                r#"use "test:second.oal"; use "test:first.oal"; res / ( get -> @a );"#,
+            )?)
+        } else {
+            unreachable!()
+        }
+    };
+
+    for _ in 0..10 {
+        let mods = load(main, loader, compile)?;
+        let spec = Spec::try_from(&mods)?;
+        assert_eq!(
+            spec.origins.get(&Ident::from("@a")).map(|s| &s.loc),
+            Some(first)
+        );
+    }
+
+    anyhow::Ok(())
+}
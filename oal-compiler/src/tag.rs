@@ -1,4 +1,5 @@
 use oal_syntax::ast;
+use std::fmt::{Display, Formatter};
 
 #[derive(PartialEq, Clone, Debug)]
 pub struct FuncTag {
@@ -45,6 +46,25 @@ impl Tag {
     }
 }
 
+impl Display for Tag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tag::Func(func) => {
+                for b in func.bindings.iter() {
+                    if let Tag::Func(_) = b {
+                        write!(f, "({}) -> ", b)?;
+                    } else {
+                        write!(f, "{} -> ", b)?;
+                    }
+                }
+                write!(f, "{}", func.range)
+            }
+            Tag::Var(n) => write!(f, "t{}", n),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
 impl From<&ast::Literal> for Tag {
     fn from(l: &ast::Literal) -> Self {
         match l {
@@ -0,0 +1,22 @@
+//! Fixtures shared by the tests of the crate.
+
+use crate::compile::compile;
+use crate::spec::Spec;
+use crate::{Locator, ModuleSet, Program};
+use oal_syntax::parse;
+
+/// Compiles a program as the main module of a set.
+pub fn compile_main(code: &str) -> anyhow::Result<ModuleSet> {
+    let loc = Locator::try_from("test:main")?;
+    let mut mods = ModuleSet::new(loc.clone());
+    let prg: Program = parse(code)?;
+    let prg = compile(&mods, &loc, prg)?;
+    mods.insert(loc, prg);
+    anyhow::Ok(mods)
+}
+
+/// Compiles a program and evaluates its specification.
+pub fn eval(code: &str) -> anyhow::Result<Spec> {
+    let mods = compile_main(code)?;
+    anyhow::Ok(Spec::try_from(&mods)?)
+}
@@ -555,14 +555,11 @@ pub struct Property<T> {
 impl<T: AsExpr> FromPair for Property<T> {
     fn from_pair(p: Pair) -> Self {
         let mut inner = p.into_inner();
-        let name = inner
-            .next()
-            .unwrap()
-            .into_inner()
-            .next()
-            .unwrap()
-            .as_str()
-            .into();
+        let name = inner.next().unwrap().into_inner().next().unwrap();
+        let name = match name.as_rule() {
+            Rule::literal_str => name.into_inner().next().unwrap().as_str().into(),
+            _ => name.as_str().into(),
+        };
         let val = Box::new(inner.next().unwrap().into_expr());
         Property { name, val }
     }
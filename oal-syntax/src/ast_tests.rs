@@ -551,6 +551,21 @@ fn parse_property_decl() {
     }
 }
 
+#[test]
+fn parse_quoted_property_decl() {
+    let d: Program = parse(r#"let a = '"first.name" str;"#).expect("parsing failed");
+
+    if let Statement::Decl(decl) = d.stmts.first().unwrap() {
+        if let Expr::Property(prop) = decl.expr.as_node().as_expr() {
+            assert_eq!(prop.name.as_ref(), "first.name");
+        } else {
+            panic!("expected property expression");
+        }
+    } else {
+        panic!("expected declaration");
+    }
+}
+
 #[test]
 fn parse_literal_number_decl() {
     let d: Program = parse("let a = 404;").expect("parsing failed");
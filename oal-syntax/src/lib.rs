@@ -2,10 +2,13 @@ pub mod ast;
 pub mod atom;
 pub mod errors;
 mod parser;
+pub mod print;
 pub mod span;
 
 #[cfg(test)]
 mod ast_tests;
+#[cfg(test)]
+mod print_tests;
 
 pub use self::parser::Parser;
 pub use self::parser::Rule;
@@ -0,0 +1,215 @@
+use crate::ast::*;
+use crate::atom::{HttpStatus, HttpStatusRange, Method, Primitive};
+use enum_map::EnumMap;
+
+/// Prints an expression back as source text.
+///
+/// Inline annotations are kept. Lambdas, which only come from declarations,
+/// are printed as `fun x y = body` and cannot be parsed back.
+pub fn print<T: AsRefNode>(e: &T) -> String {
+    let node = e.as_node();
+    match &node.ann {
+        Some(ann) if is_term(e) => format!("{} `{}`", expr(e), ann.text),
+        Some(ann) => format!("({}) `{}`", expr(e), ann.text),
+        None => expr(e),
+    }
+}
+
+/// Prints a property name, quoted unless made of keyname characters only.
+fn prop_name(name: &str) -> String {
+    let is_keyname = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "@$-_".contains(c));
+    if is_keyname {
+        name.to_owned()
+    } else {
+        format!("\"{}\"", name)
+    }
+}
+
+/// Tells whether an expression can be printed in a term position without parentheses.
+fn is_term<T: AsRefNode>(e: &T) -> bool {
+    matches!(
+        e.as_node().as_expr(),
+        Expr::Lit(_)
+            | Expr::Prim(_)
+            | Expr::Uri(_)
+            | Expr::Array(_)
+            | Expr::Property(_)
+            | Expr::Object(_)
+            | Expr::Content(_)
+            | Expr::Var(_)
+            | Expr::Binding(_)
+    )
+}
+
+fn term<T: AsRefNode>(e: &T) -> String {
+    if is_term(e) || e.as_node().ann.is_some() {
+        print(e)
+    } else {
+        format!("({})", print(e))
+    }
+}
+
+fn expr<T: AsRefNode>(e: &T) -> String {
+    match e.as_node().as_expr() {
+        Expr::Lit(l) => literal(l),
+        Expr::Prim(p) => primitive(p).to_owned(),
+        Expr::Rel(rel) => format!(
+            "{} ( {} )",
+            term(rel.uri.as_ref()),
+            rel.xfers.iter().map(print).collect::<Vec<_>>().join(", ")
+        ),
+        Expr::Uri(uri) => {
+            let params = uri
+                .params
+                .as_ref()
+                .map(|p| format!("?{}", print(p.as_ref())));
+            if uri.path.is_empty() && params.is_none() {
+                "uri".to_owned()
+            } else {
+                let path: String = uri
+                    .path
+                    .iter()
+                    .map(|s| match s {
+                        UriSegment::Literal(l) => format!("/{}", l),
+                        UriSegment::Variable(v) => format!("/{{ {} }}", print(v)),
+                    })
+                    .collect();
+                let path = if path.is_empty() {
+                    "/".to_owned()
+                } else {
+                    path
+                };
+                format!("{}{}", path, params.unwrap_or_default())
+            }
+        }
+        Expr::Array(array) => format!("[{}]", print(array.item.as_ref())),
+        Expr::Property(prop) => format!(
+            "'{} {}",
+            prop_name(prop.name.as_ref()),
+            term(prop.val.as_ref())
+        ),
+        Expr::Object(obj) => {
+            if obj.props.is_empty() {
+                "{}".to_owned()
+            } else {
+                let props: Vec<_> = obj.props.iter().map(print).collect();
+                format!("{{ {} }}", props.join(", "))
+            }
+        }
+        Expr::Content(cnt) => {
+            let props = [
+                ("status", &cnt.status),
+                ("media", &cnt.media),
+                ("headers", &cnt.headers),
+            ];
+            let mut parts: Vec<_> = props
+                .iter()
+                .filter_map(|(k, v)| v.as_ref().map(|v| format!("{}={}", k, term(v.as_ref()))))
+                .collect();
+            match &cnt.schema {
+                Some(s) => parts.push(print(s.as_ref())),
+                // A trailing comma tells content properties apart from a schema.
+                None if !parts.is_empty() => parts.push("".to_owned()),
+                None => {}
+            }
+            format!("<{}>", parts.join(", ").trim_end())
+        }
+        Expr::Xfer(xfer) => {
+            let mut s = methods(&xfer.methods);
+            if let Some(params) = &xfer.params {
+                s.push(' ');
+                s.push_str(&print(params.as_ref()));
+            }
+            if let Some(domain) = &xfer.domain {
+                s.push_str(" : ");
+                s.push_str(&term(domain.as_ref()));
+            }
+            s.push_str(" -> ");
+            s.push_str(&operand(xfer.ranges.as_ref(), Some(Operator::Range)));
+            s
+        }
+        Expr::Op(op) => {
+            let symbol = match op.op {
+                Operator::Join => " & ",
+                Operator::Any => " ~ ",
+                Operator::Sum => " | ",
+                Operator::Range => " :: ",
+            };
+            op.exprs
+                .iter()
+                .map(|e| operand(e, None))
+                .collect::<Vec<_>>()
+                .join(symbol)
+        }
+        Expr::Var(v) => v.to_string(),
+        Expr::Lambda(lambda) => format!(
+            "fun {} = {}",
+            lambda
+                .bindings
+                .iter()
+                .map(print)
+                .collect::<Vec<_>>()
+                .join(" "),
+            print(lambda.body.as_ref())
+        ),
+        Expr::App(app) => format!(
+            "{} {}",
+            app.name,
+            app.args.iter().map(term).collect::<Vec<_>>().join(" ")
+        ),
+        Expr::Binding(b) => b.to_string(),
+    }
+}
+
+/// Prints the operand of a variadic operator, or of the given operator if any.
+fn operand<T: AsRefNode>(e: &T, op: Option<Operator>) -> String {
+    match e.as_node().as_expr() {
+        Expr::Op(o) if Some(o.op) == op && e.as_node().ann.is_none() => print(e),
+        Expr::Op(_) | Expr::Xfer(_) | Expr::Rel(_) | Expr::Lambda(_) => term(e),
+        _ => print(e),
+    }
+}
+
+fn literal(l: &Literal) -> String {
+    match l {
+        Literal::Text(t) => format!("\"{}\"", t),
+        Literal::Number(n) => n.to_string(),
+        Literal::Status(HttpStatus::Code(c)) => c.to_string(),
+        Literal::Status(HttpStatus::Range(r)) => match r {
+            HttpStatusRange::Info => "1XX",
+            HttpStatusRange::Success => "2XX",
+            HttpStatusRange::Redirect => "3XX",
+            HttpStatusRange::ClientError => "4XX",
+            HttpStatusRange::ServerError => "5XX",
+        }
+        .to_owned(),
+    }
+}
+
+fn primitive(p: &Primitive) -> &'static str {
+    match p {
+        Primitive::Number => "num",
+        Primitive::String => "str",
+        Primitive::Boolean => "bool",
+        Primitive::Integer => "int",
+    }
+}
+
+fn methods(m: &EnumMap<Method, bool>) -> String {
+    m.iter()
+        .filter(|(_, enabled)| **enabled)
+        .map(|(m, _)| match m {
+            Method::Get => "get",
+            Method::Put => "put",
+            Method::Post => "post",
+            Method::Patch => "patch",
+            Method::Delete => "delete",
+            Method::Options => "options",
+            Method::Head => "head",
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,77 @@
+use crate::ast::*;
+use crate::parse;
+use crate::print::print;
+
+#[derive(Clone, Debug, PartialEq)]
+struct TestExpr(NodeExpr<TestExpr>);
+
+impl From<NodeExpr<TestExpr>> for TestExpr {
+    fn from(e: NodeExpr<TestExpr>) -> Self {
+        TestExpr(e)
+    }
+}
+
+impl AsRefNode for TestExpr {
+    fn as_node(&self) -> &NodeExpr<TestExpr> {
+        &self.0
+    }
+}
+
+impl AsMutNode for TestExpr {
+    fn as_node_mut(&mut self) -> &mut NodeExpr<TestExpr> {
+        &mut self.0
+    }
+}
+
+type Program = crate::ast::Program<TestExpr>;
+
+fn printed(code: &str) -> Vec<String> {
+    let prg: Program = parse(code).expect("parsing failed");
+    prg.stmts
+        .iter()
+        .filter_map(|s| match s {
+            Statement::Decl(d) if !matches!(d.expr.as_node().as_expr(), Expr::Lambda(_)) => {
+                Some(print(&d.expr))
+            }
+            Statement::Res(r) => Some(print(&r.rel)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn print_expressions() {
+    let code = r#"
+        let a = { 'firstName str `title: "First name"`, 'tags [str] };
+        let b = @a & ({ 'age int } | num);
+        let c = <status=200, media="application/json", headers={ 'ETag str }, @a> :: <status=404,>;
+        let d = /some/{ 'id str }/path?{ 'q str } ( get, put { 'n num } : <@a> -> c );
+        let e = f { 'x uri } (g @a);
+        let g = { '"first.name" str, '"a b" num };
+    "#;
+
+    assert_eq!(
+        printed(code),
+        vec![
+            r#"{ 'firstName str `title: "First name"`, 'tags [str] }"#,
+            "@a & ({ 'age int } | num)",
+            r#"<status=200, media="application/json", headers={ 'ETag str }, @a> :: <status=404,>"#,
+            "/some/{ 'id str }/path?{ 'q str } ( get, put { 'n num } : <@a> -> c )",
+            "f { 'x uri } (g @a)",
+            r#"{ '"first.name" str, '"a b" num }"#,
+        ]
+    );
+}
+
+#[test]
+fn print_parse_roundtrip() {
+    let code = std::fs::read_to_string("../examples/main.oal").expect("reading failed");
+    let first = printed(&code);
+    let source: String = first
+        .iter()
+        .enumerate()
+        .map(|(i, e)| format!("let x{} = {};\n", i, e))
+        .collect();
+
+    assert_eq!(printed(&source), first);
+}
@@ -0,0 +1,56 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Kind {
+    /// No relation matches the path
+    NoRelation,
+    /// The relation has no transfer for the method
+    NoTransfer,
+    /// The transfer has no response for the status
+    UnexpectedStatus,
+    /// The value is not of the expected type
+    InvalidType,
+    /// A number is out of bounds or not a multiple of the expected factor
+    OutOfRange,
+    /// A string does not match the expected pattern
+    NoMatch,
+    /// A string is not one of the enumerated values
+    NotEnumerated,
+    /// A required property or parameter is missing
+    Missing,
+    /// None or several of the alternatives of a schema match the value
+    Alternatives,
+    /// The schema cannot be checked, like a pattern which is not a regular expression
+    InvalidSchema,
+}
+
+/// A mismatch between a value and its specification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub kind: Kind,
+    /// The JSON pointer to the offending value
+    pub pointer: String,
+    pub message: String,
+}
+
+impl Error {
+    pub fn new<S: Into<String>>(kind: Kind, pointer: &str, message: S) -> Error {
+        Error {
+            kind,
+            pointer: pointer.to_owned(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.pointer, self.message)
+        }
+    }
+}
+
+impl std::error::Error for Error {}
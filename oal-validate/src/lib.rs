@@ -0,0 +1,103 @@
+mod errors;
+mod route;
+mod schema;
+
+#[cfg(test)]
+mod route_tests;
+#[cfg(test)]
+mod schema_tests;
+#[cfg(test)]
+mod tests;
+#[cfg(test)]
+mod validate_tests;
+
+pub use crate::errors::{Error, Kind};
+pub use crate::route::{route, Route};
+pub use crate::schema::Validator;
+
+use oal_compiler::spec::{Content, Ranges, Spec};
+use oal_syntax::atom::{HttpStatus, HttpStatusRange, Method};
+use serde_json::Value;
+
+/// Tells whether a status code belongs to a range.
+fn in_range(range: HttpStatusRange, code: u16) -> bool {
+    let class = match range {
+        HttpStatusRange::Info => 1,
+        HttpStatusRange::Success => 2,
+        HttpStatusRange::Redirect => 3,
+        HttpStatusRange::ClientError => 4,
+        HttpStatusRange::ServerError => 5,
+    };
+    code / 100 == class
+}
+
+/// Returns the content of the response with the given status.
+///
+/// A content with the exact status code takes precedence over a range,
+/// which takes precedence over the content with no status.
+pub fn response(ranges: &Ranges, code: u16) -> Option<&Content> {
+    let exact = ranges
+        .values()
+        .find(|c| matches!(c.status, Some(HttpStatus::Code(s)) if s.get() == code));
+    let range = || {
+        ranges
+            .values()
+            .find(|c| matches!(c.status, Some(HttpStatus::Range(r)) if in_range(r, code)))
+    };
+    let default = || ranges.values().find(|c| c.status.is_none());
+    exact.or_else(range).or_else(default)
+}
+
+/// Validates the body of a request, or of a response if a status is given, against a specification.
+///
+/// The path is matched against the relations and the values of its variables are validated too.
+/// Errors in the body are located by a JSON pointer relative to the body,
+/// and errors in path variables by a pointer made of the variable name.
+pub fn validate(
+    spec: &Spec,
+    method: Method,
+    path: &str,
+    status: Option<u16>,
+    body: &Value,
+) -> Vec<Error> {
+    let found = match route(spec, path) {
+        Some(found) => found,
+        None => return vec![Error::new(Kind::NoRelation, "", "no relation for the path")],
+    };
+    let xfer = match &found.rel.xfers[method] {
+        Some(xfer) => xfer,
+        None => {
+            return vec![Error::new(
+                Kind::NoTransfer,
+                "",
+                "no transfer for the method",
+            )]
+        }
+    };
+    let validator = Validator::new(&spec.refs);
+    let values: Vec<_> = found
+        .vars
+        .iter()
+        .map(|(p, v)| (p.name.as_ref().to_owned(), v.to_string()))
+        .collect();
+    let mut errors = validator.params(found.vars.iter().map(|(p, _)| *p), &values);
+    let content = match status {
+        None => &xfer.domain,
+        Some(code) => match response(&xfer.ranges, code) {
+            Some(content) => content,
+            None => {
+                let msg = format!("no response for status {}", code);
+                errors.push(Error::new(Kind::UnexpectedStatus, "", msg));
+                return errors;
+            }
+        },
+    };
+    match &content.schema {
+        Some(schema) => errors.extend(validator.schema(schema, body, "")),
+        None if !body.is_null() => {
+            errors.push(Error::new(Kind::InvalidType, "", "expected no body"))
+        }
+        None => {}
+    }
+    errors
+}
@@ -0,0 +1,35 @@
+use oal_compiler::spec::{Property, Relation, Spec, UriSegment};
+use percent_encoding::percent_decode_str;
+
+/// A relation matching a concrete path, along with the decoded values of its variables.
+#[derive(Debug)]
+pub struct Route<'a> {
+    pub rel: &'a Relation,
+    pub vars: Vec<(&'a Property, String)>,
+}
+
+/// Finds the relation of a concrete path, without query string.
+///
+/// Segments are percent-decoded before matching. Literal segments take precedence over variables.
+pub fn route<'a>(spec: &'a Spec, path: &str) -> Option<Route<'a>> {
+    let segments: Vec<_> = path
+        .strip_prefix('/')?
+        .split('/')
+        .map(|s| percent_decode_str(s).decode_utf8_lossy().into_owned())
+        .collect();
+    let found = spec.rels.values().filter_map(|rel| {
+        if rel.uri.path.len() != segments.len() {
+            return None;
+        }
+        let mut vars = Vec::new();
+        for (s, seg) in rel.uri.path.iter().zip(segments.iter()) {
+            match s {
+                UriSegment::Literal(l) if l.as_ref() == seg.as_str() => {}
+                UriSegment::Variable(p) if !seg.is_empty() => vars.push((p.as_ref(), seg.clone())),
+                _ => return None,
+            }
+        }
+        Some(Route { rel, vars })
+    });
+    found.min_by_key(|r| r.vars.len())
+}
@@ -0,0 +1,52 @@
+use crate::route;
+use crate::tests::eval;
+
+#[test]
+fn route_paths() -> anyhow::Result<()> {
+    let code = r#"
+        res / ( get -> str );
+        res /a/{ 'id int } ( get -> int );
+        res /a/me ( get -> bool );
+        res /a/{ 'id int }/b ( put -> str );
+    "#;
+
+    let spec = eval(code)?;
+
+    let r = route(&spec, "/").expect("root should match");
+    assert!(r.vars.is_empty());
+
+    let r = route(&spec, "/a/12").expect("variable should match");
+    assert_eq!(r.vars.len(), 1);
+    assert_eq!(r.vars[0].0.name.as_ref(), "id");
+    assert_eq!(r.vars[0].1, "12");
+
+    let r = route(&spec, "/a/me").expect("literal should match");
+    assert!(r.vars.is_empty(), "literal should take precedence");
+
+    let r = route(&spec, "/a/12/b").expect("nested path should match");
+    assert_eq!(r.vars[0].1, "12");
+
+    assert!(route(&spec, "/b").is_none());
+    assert!(route(&spec, "/a/").is_none());
+    assert!(route(&spec, "a").is_none());
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn route_decoding() -> anyhow::Result<()> {
+    let code = r#"
+        res /files/{ 'name str } ( get -> str );
+        res /a-b ( get -> str );
+    "#;
+
+    let spec = eval(code)?;
+
+    let r = route(&spec, "/files/my%20file%2Ftxt").expect("variable should match");
+    assert_eq!(r.vars[0].1, "my file/txt");
+
+    let r = route(&spec, "/a%2Db").expect("literal should match");
+    assert!(r.vars.is_empty());
+
+    anyhow::Ok(())
+}
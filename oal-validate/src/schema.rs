@@ -0,0 +1,209 @@
+use crate::errors::{Error, Kind};
+use oal_compiler::spec::{self, References};
+use oal_syntax::ast::Operator;
+use regex::Regex;
+use serde_json::Value;
+
+/// Escapes a key as a JSON pointer token.
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Tells whether a number is a multiple of another, up to rounding errors.
+fn is_multiple(n: f64, m: f64) -> bool {
+    let q = n / m;
+    (q - q.round()).abs() < 1e-9
+}
+
+/// A validator of JSON values against the schemas of a specification.
+pub struct Validator<'a> {
+    refs: &'a References,
+}
+
+impl<'a> Validator<'a> {
+    pub fn new(refs: &'a References) -> Self {
+        Validator { refs }
+    }
+
+    fn number(&self, p: &spec::PrimNumber, value: &Value, ptr: &str) -> Vec<Error> {
+        let n = match value.as_f64() {
+            Some(n) => n,
+            None => return vec![Error::new(Kind::InvalidType, ptr, "expected a number")],
+        };
+        let mut errors = Vec::new();
+        if let Some(min) = p.minimum.filter(|min| n < *min) {
+            errors.push(Error::new(Kind::OutOfRange, ptr, format!("below {}", min)));
+        }
+        if let Some(max) = p.maximum.filter(|max| n > *max) {
+            errors.push(Error::new(Kind::OutOfRange, ptr, format!("above {}", max)));
+        }
+        if let Some(m) = p.multiple_of.filter(|m| *m != 0.0 && !is_multiple(n, *m)) {
+            let msg = format!("not a multiple of {}", m);
+            errors.push(Error::new(Kind::OutOfRange, ptr, msg));
+        }
+        errors
+    }
+
+    fn integer(&self, p: &spec::PrimInteger, value: &Value, ptr: &str) -> Vec<Error> {
+        let integral = value
+            .as_f64()
+            .filter(|f| f.fract() == 0.0)
+            .map(|f| f as i64);
+        let n = match value.as_i64().or(integral) {
+            Some(n) => n,
+            None => return vec![Error::new(Kind::InvalidType, ptr, "expected an integer")],
+        };
+        let mut errors = Vec::new();
+        if let Some(min) = p.minimum.filter(|min| n < *min) {
+            errors.push(Error::new(Kind::OutOfRange, ptr, format!("below {}", min)));
+        }
+        if let Some(max) = p.maximum.filter(|max| n > *max) {
+            errors.push(Error::new(Kind::OutOfRange, ptr, format!("above {}", max)));
+        }
+        if let Some(m) = p.multiple_of.filter(|m| *m != 0 && n % m != 0) {
+            let msg = format!("not a multiple of {}", m);
+            errors.push(Error::new(Kind::OutOfRange, ptr, msg));
+        }
+        errors
+    }
+
+    fn string(&self, p: &spec::PrimString, value: &Value, ptr: &str) -> Vec<Error> {
+        let s = match value.as_str() {
+            Some(s) => s,
+            None => return vec![Error::new(Kind::InvalidType, ptr, "expected a string")],
+        };
+        let mut errors = Vec::new();
+        if let Some(pattern) = &p.pattern {
+            match Regex::new(pattern) {
+                Ok(re) if re.is_match(s) => {}
+                Ok(_) => {
+                    let msg = format!("does not match {}", pattern);
+                    errors.push(Error::new(Kind::NoMatch, ptr, msg));
+                }
+                Err(err) => errors.push(Error::new(Kind::InvalidSchema, ptr, err.to_string())),
+            }
+        }
+        if !p.enumeration.is_empty() && !p.enumeration.iter().any(|e| e == s) {
+            let msg = format!("expected one of {}", p.enumeration.join(", "));
+            errors.push(Error::new(Kind::NotEnumerated, ptr, msg));
+        }
+        errors
+    }
+
+    fn object(&self, obj: &spec::Object, value: &Value, ptr: &str) -> Vec<Error> {
+        let o = match value.as_object() {
+            Some(o) => o,
+            None => return vec![Error::new(Kind::InvalidType, ptr, "expected an object")],
+        };
+        let mut errors = Vec::new();
+        for p in obj.props.iter() {
+            let name = p.name.as_ref();
+            let prop_ptr = format!("{}/{}", ptr, escape_pointer(name));
+            match o.get(name) {
+                Some(v) => errors.extend(self.schema(&p.schema, v, &prop_ptr)),
+                None if p.schema.required == Some(true) => {
+                    errors.push(Error::new(Kind::Missing, &prop_ptr, "missing property"))
+                }
+                None => {}
+            }
+        }
+        errors
+    }
+
+    fn array(&self, array: &spec::Array, value: &Value, ptr: &str) -> Vec<Error> {
+        match value.as_array() {
+            Some(items) => items
+                .iter()
+                .enumerate()
+                .flat_map(|(i, item)| self.schema(&array.item, item, &format!("{}/{}", ptr, i)))
+                .collect(),
+            None => vec![Error::new(Kind::InvalidType, ptr, "expected an array")],
+        }
+    }
+
+    fn operation(&self, op: &spec::VariadicOp, value: &Value, ptr: &str) -> Vec<Error> {
+        if op.op == Operator::Join {
+            return op
+                .schemas
+                .iter()
+                .flat_map(|s| self.schema(s, value, ptr))
+                .collect();
+        }
+        let matching = op
+            .schemas
+            .iter()
+            .filter(|s| self.schema(s, value, ptr).is_empty())
+            .count();
+        match (op.op, matching) {
+            (_, 0) => vec![Error::new(
+                Kind::Alternatives,
+                ptr,
+                "matches none of the alternatives",
+            )],
+            (Operator::Sum, n) if n > 1 => vec![Error::new(
+                Kind::Alternatives,
+                ptr,
+                format!("matches {} exclusive alternatives", n),
+            )],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Checks a value against a schema, the pointer locating the value in its document.
+    pub fn schema(&self, s: &spec::Schema, value: &Value, ptr: &str) -> Vec<Error> {
+        match &s.expr {
+            spec::SchemaExpr::Num(p) => self.number(p, value, ptr),
+            spec::SchemaExpr::Int(p) => self.integer(p, value, ptr),
+            spec::SchemaExpr::Str(p) => self.string(p, value, ptr),
+            spec::SchemaExpr::Bool(_) if value.is_boolean() => Vec::new(),
+            spec::SchemaExpr::Bool(_) => {
+                vec![Error::new(Kind::InvalidType, ptr, "expected a boolean")]
+            }
+            spec::SchemaExpr::Uri(_) | spec::SchemaExpr::Rel(_) if value.is_string() => Vec::new(),
+            spec::SchemaExpr::Uri(_) | spec::SchemaExpr::Rel(_) => {
+                vec![Error::new(Kind::InvalidType, ptr, "expected a URI")]
+            }
+            spec::SchemaExpr::Array(array) => self.array(array, value, ptr),
+            spec::SchemaExpr::Object(obj) => self.object(obj, value, ptr),
+            spec::SchemaExpr::Op(op) => self.operation(op, value, ptr),
+            spec::SchemaExpr::Ref(name) => match self.refs.get(name) {
+                Some(spec::Reference::Schema(referenced)) => self.schema(referenced, value, ptr),
+                None => {
+                    let msg = format!("unknown reference {}", name);
+                    vec![Error::new(Kind::InvalidSchema, ptr, msg)]
+                }
+            },
+        }
+    }
+
+    /// Checks the values of parameters given as strings, by name.
+    ///
+    /// Strings are taken as is for string-like schemas and read as JSON values otherwise.
+    /// Each parameter is located by a pointer made of its name.
+    pub fn params<'p, I>(&self, props: I, values: &[(String, String)]) -> Vec<Error>
+    where
+        I: IntoIterator<Item = &'p spec::Property>,
+    {
+        let mut errors = Vec::new();
+        for prop in props {
+            let name = prop.name.as_ref();
+            let ptr = format!("/{}", escape_pointer(name));
+            match values.iter().find(|(n, _)| n == name) {
+                Some((_, raw)) => {
+                    let value = match &prop.schema.expr {
+                        spec::SchemaExpr::Str(_)
+                        | spec::SchemaExpr::Uri(_)
+                        | spec::SchemaExpr::Rel(_) => Value::String(raw.clone()),
+                        _ => serde_json::from_str(raw).unwrap_or_else(|_| raw.clone().into()),
+                    };
+                    errors.extend(self.schema(&prop.schema, &value, &ptr))
+                }
+                None if prop.required == Some(true) => {
+                    errors.push(Error::new(Kind::Missing, &ptr, "missing parameter"))
+                }
+                None => {}
+            }
+        }
+        errors
+    }
+}
@@ -0,0 +1,118 @@
+use crate::tests::eval;
+use crate::{Kind, Validator};
+use oal_compiler::spec::Reference;
+use oal_syntax::atom::Ident;
+use serde_json::{json, Value};
+
+fn check(code: &str, name: &str, value: Value) -> anyhow::Result<Vec<(Kind, String)>> {
+    let spec = eval(code)?;
+    let Reference::Schema(schema) = &spec.refs[&Ident::from(name)];
+    let errors = Validator::new(&spec.refs).schema(schema, &value, "");
+    anyhow::Ok(errors.into_iter().map(|e| (e.kind, e.pointer)).collect())
+}
+
+#[test]
+fn schema_primitives() -> anyhow::Result<()> {
+    let code = r#"
+        let @a = {
+          'n num `minimum: 1.5, maximum: 10, multipleOf: 0.5`
+        , 'i int `minimum: 0, multipleOf: 3`
+        , 's str `pattern: "^[a-z]+$"`
+        , 'e str `enum: [x, y]`
+        , 'b bool
+        , 'u uri
+        , 'l [int]
+        };
+        res / ( get -> @a );
+    "#;
+
+    let valid = json!({
+        "n": 2.5, "i": 6, "s": "abc", "e": "y", "b": false, "u": "/x", "l": [1, 2.0]
+    });
+    assert!(check(code, "@a", valid)?.is_empty());
+
+    let invalid = json!({
+        "n": 10.2, "i": -4, "s": "ABC", "e": "z", "b": 1, "u": 2, "l": [1, 1.5]
+    });
+    assert_eq!(
+        check(code, "@a", invalid)?,
+        vec![
+            (Kind::OutOfRange, "/n".to_owned()),
+            (Kind::OutOfRange, "/n".to_owned()),
+            (Kind::OutOfRange, "/i".to_owned()),
+            (Kind::OutOfRange, "/i".to_owned()),
+            (Kind::NoMatch, "/s".to_owned()),
+            (Kind::NotEnumerated, "/e".to_owned()),
+            (Kind::InvalidType, "/b".to_owned()),
+            (Kind::InvalidType, "/u".to_owned()),
+            (Kind::InvalidType, "/l/1".to_owned()),
+        ]
+    );
+
+    assert_eq!(
+        check(code, "@a", json!([]))?,
+        vec![(Kind::InvalidType, "".to_owned())]
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn schema_required() -> anyhow::Result<()> {
+    let code = r#"
+        let @a = { 'id int `required: true`, 'name str, 'tag str `required: true` };
+        res / ( get -> @a );
+    "#;
+
+    assert!(check(code, "@a", json!({ "id": 1, "tag": "x" }))?.is_empty());
+    assert_eq!(
+        check(code, "@a", json!({ "name": "x" }))?,
+        vec![
+            (Kind::Missing, "/id".to_owned()),
+            (Kind::Missing, "/tag".to_owned()),
+        ]
+    );
+
+    anyhow::Ok(())
+}
+
+#[test]
+fn schema_operators() -> anyhow::Result<()> {
+    let code = r#"
+        let @a = { 'id int `required: true` };
+        let @b = { 'name str `required: true` };
+        let @join = @a & @b;
+        let @sum = int | num;
+        let @any = @a ~ @b;
+        res / ( get -> @join, put -> @sum, post -> @any );
+    "#;
+
+    assert!(check(code, "@join", json!({ "id": 1, "name": "x" }))?.is_empty());
+    assert_eq!(
+        check(code, "@join", json!({ "id": "1" }))?,
+        vec![
+            (Kind::InvalidType, "/id".to_owned()),
+            (Kind::Missing, "/name".to_owned()),
+        ]
+    );
+
+    assert!(check(code, "@sum", json!(1.5))?.is_empty());
+    assert_eq!(
+        check(code, "@sum", json!(1))?,
+        vec![(Kind::Alternatives, "".to_owned())],
+        "an integer is both an int and a num"
+    );
+    assert_eq!(
+        check(code, "@sum", json!("1"))?,
+        vec![(Kind::Alternatives, "".to_owned())]
+    );
+
+    assert!(check(code, "@any", json!({ "id": 1, "name": "x" }))?.is_empty());
+    assert!(check(code, "@any", json!({ "name": "x" }))?.is_empty());
+    assert_eq!(
+        check(code, "@any", json!({}))?,
+        vec![(Kind::Alternatives, "".to_owned())]
+    );
+
+    anyhow::Ok(())
+}
@@ -0,0 +1,69 @@
+use crate::tests::eval;
+use crate::{validate, Kind};
+use oal_syntax::atom::Method;
+use serde_json::{json, Value};
+
+#[test]
+fn validate_exchanges() -> anyhow::Result<()> {
+    let code = r#"
+        let @item = { 'id int `required: true`, 'name str };
+        let @error = { 'message str `required: true` };
+        res /items/{ 'id int `minimum: 1` } (
+          get -> <status=200, @item> :: <status=404, @error> :: <status=5XX, @error>,
+          put : @item -> <status=204,>
+        );
+    "#;
+
+    let spec = eval(code)?;
+    let kinds = |method, path, status, body: Value| {
+        validate(&spec, method, path, status, &body)
+            .into_iter()
+            .map(|e| (e.kind, e.pointer))
+            .collect::<Vec<_>>()
+    };
+
+    assert!(kinds(Method::Get, "/items/1", Some(200), json!({ "id": 1 })).is_empty());
+    assert!(kinds(
+        Method::Get,
+        "/items/1",
+        Some(404),
+        json!({ "message": "x" })
+    )
+    .is_empty());
+    assert!(kinds(
+        Method::Get,
+        "/items/1",
+        Some(503),
+        json!({ "message": "x" })
+    )
+    .is_empty());
+    assert!(kinds(Method::Put, "/items/1", None, json!({ "id": 1 })).is_empty());
+    assert!(kinds(Method::Put, "/items/1", Some(204), Value::Null).is_empty());
+
+    assert_eq!(
+        kinds(Method::Get, "/items/0", Some(200), json!({ "name": 1 })),
+        vec![
+            (Kind::OutOfRange, "/id".to_owned()),
+            (Kind::Missing, "/id".to_owned()),
+            (Kind::InvalidType, "/name".to_owned()),
+        ]
+    );
+    assert_eq!(
+        kinds(Method::Get, "/items/1", Some(201), json!({ "id": 1 })),
+        vec![(Kind::UnexpectedStatus, "".to_owned())]
+    );
+    assert_eq!(
+        kinds(Method::Put, "/items/1", Some(204), json!({})),
+        vec![(Kind::InvalidType, "".to_owned())]
+    );
+    assert_eq!(
+        kinds(Method::Delete, "/items/1", None, Value::Null),
+        vec![(Kind::NoTransfer, "".to_owned())]
+    );
+    assert_eq!(
+        kinds(Method::Get, "/things", Some(200), Value::Null),
+        vec![(Kind::NoRelation, "".to_owned())]
+    );
+
+    anyhow::Ok(())
+}